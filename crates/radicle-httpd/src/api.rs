@@ -1,7 +1,9 @@
 use std::collections::{BTreeMap, HashMap};
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 
-use axum::response::{IntoResponse, Json};
+use axum::http::HeaderMap;
+use axum::response::{IntoResponse, Json, Response};
 use axum::routing::get;
 use axum::Router;
 use serde_json::{json, Value};
@@ -16,20 +18,31 @@ use radicle::storage::{ReadRepository, ReadStorage};
 use radicle::{git, web, Profile};
 use tokio::sync::RwLock;
 
+pub(crate) mod auth;
+pub(crate) mod badges;
 mod error;
+pub(crate) mod events;
 mod json;
+pub(crate) mod pool;
 pub(crate) mod query;
+pub(crate) mod summaries;
 mod v1;
+pub(crate) mod webhooks;
+mod xml;
 
 pub(crate) use radicle_search::query::SearchClient;
 
 use crate::api::error::Error;
-use crate::cache::Cache;
+use crate::axum_extra::{immutable_json, ETag};
+use crate::bundles::Bundles;
+use crate::cache::{self, Cache};
 use crate::Options;
 
 pub const RADICLE_VERSION: &str = env!("RADICLE_VERSION");
 // This version has to be updated on every breaking change to the radicle-httpd API.
 pub const API_VERSION: &str = "6.1.0";
+/// Bytes in a mebibyte, the unit of cache budgets.
+const MIB: usize = 1024 * 1024;
 
 /// Thread-safe wrapper around radicle's web configuration.
 ///
@@ -79,6 +92,20 @@ pub struct Context {
     /// [`RepoId`]. Used to resolve alias path segments to a repo and to
     /// advertise a repo's short name in its info.
     repo_aliases: Arc<HashMap<String, RepoId>>,
+    /// Whether the authenticated write API is enabled via `--write`.
+    write: bool,
+    /// Write API sessions.
+    sessions: auth::Sessions,
+    /// Public repo events relayed from the node.
+    events: events::Events,
+    /// Outgoing webhooks, configured under `web.webhooks`.
+    webhooks: webhooks::Webhooks,
+    /// Precomputed summaries of public repos, for listings.
+    summaries: summaries::Summaries,
+    /// Limits on the blocking work done for requests.
+    pool: pool::Pool,
+    /// Prebuilt clone bundles, if a bundle directory is configured.
+    bundles: Option<Bundles>,
 }
 
 impl Context {
@@ -116,12 +143,70 @@ impl Context {
             None => None,
         };
 
+        // Like search, the cache directory is an optimization: failing to open
+        // it is non-fatal.
+        let disk = match &options.cache_dir {
+            Some(_) if options.cache.is_none() => {
+                tracing::warn!("the cache directory requires the response cache, ignoring it");
+                None
+            }
+            Some(dir) => {
+                let size = dir.size.get().saturating_mul(MIB) as u64;
+                match cache::disk::Disk::open(&dir.path, size) {
+                    Ok(disk) => {
+                        tracing::info!("cache directory enabled: {}", dir.path.display());
+                        Some(disk)
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "failed to open cache directory {}, continuing without it: {e}",
+                            dir.path.display()
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+        // Bundles are an optimization too.
+        let bundles = match &options.bundle_dir {
+            Some(dir) => match Bundles::open(dir) {
+                Ok(bundles) => {
+                    tracing::info!("clone bundles enabled: {}", dir.display());
+                    Some(bundles)
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "failed to open bundle directory {}, continuing without it: {e}",
+                        dir.display()
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        let cache = options
+            .cache
+            .and_then(|mib| NonZeroUsize::new(mib.get().saturating_mul(MIB)))
+            .map(Cache::new)
+            .map(|cache| match disk {
+                Some(disk) => cache.with_disk(disk),
+                None => cache,
+            });
+
         Ok(Self {
             profile: profile.clone(),
-            cache: options.cache.map(Cache::new),
+            cache,
             web_config,
             search,
             repo_aliases: Arc::new(options.aliases.clone()),
+            write: options.write,
+            sessions: auth::Sessions::default(),
+            events: events::Events::default(),
+            webhooks: webhooks::Webhooks::new(webhooks::load(&profile)),
+            summaries: summaries::Summaries::default(),
+            pool: pool::Pool::new(&options.blocking),
+            bundles,
         })
     }
 
@@ -148,6 +233,46 @@ impl Context {
         self.repo_aliases = Arc::new(aliases);
     }
 
+    #[cfg(test)]
+    pub fn set_write(&mut self, write: bool) {
+        self.write = write;
+    }
+
+    /// Whether the authenticated write API is enabled.
+    pub fn write_enabled(&self) -> bool {
+        self.write
+    }
+
+    /// Public repo events relayed from the node.
+    pub fn events(&self) -> &events::Events {
+        &self.events
+    }
+
+    /// The response cache, if it's enabled.
+    pub(crate) fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    /// Outgoing webhooks.
+    pub fn webhooks(&self) -> &webhooks::Webhooks {
+        &self.webhooks
+    }
+
+    /// Precomputed repo summaries.
+    pub fn summaries(&self) -> &summaries::Summaries {
+        &self.summaries
+    }
+
+    /// Limits on the blocking work done for requests.
+    pub(crate) fn pool(&self) -> &pool::Pool {
+        &self.pool
+    }
+
+    /// Prebuilt clone bundles, if they're enabled.
+    pub(crate) fn bundles(&self) -> Option<&Bundles> {
+        self.bundles.as_ref()
+    }
+
     /// The search backend client, if one is configured and reachable at
     /// startup. `None` means listing and search use the storage walk.
     pub fn search(&self) -> Option<&SearchClient> {
@@ -224,6 +349,99 @@ impl Context {
         Ok((repo, doc))
     }
 
+    /// Get a response from the response cache, if it's enabled and holds
+    /// one. Repo visibility is enforced even on cache hits.
+    pub(crate) async fn cache_get(&self, key: &cache::Key) -> Result<Option<Response>, Error> {
+        let Some(cache) = &self.cache else {
+            return Ok(None);
+        };
+        let Some(entry) = cache.get(key).await else {
+            return Ok(None);
+        };
+        let ctx = self.clone();
+        let rid = key.rid;
+        blocking(move || ctx.repo(rid).map(|_| ())).await?;
+
+        Ok(Some(immutable_json(entry.body)))
+    }
+
+    /// Serialize an immutable response, caching it if the response cache is
+    /// enabled.
+    pub(crate) async fn cache_put(
+        &self,
+        key: cache::Key,
+        value: &impl serde::Serialize,
+    ) -> Result<Response, Error> {
+        let body = axum::body::Bytes::from(serde_json::to_vec(value)?);
+        if let Some(cache) = &self.cache {
+            cache.put(key, cache::Entry::new(body.clone(), None)).await;
+        }
+        Ok(immutable_json(body))
+    }
+
+    /// Look up a mutable response, which is tagged with an entity tag.
+    /// Mutable responses are only cached while node events are relayed, as
+    /// they're what keeps the cache from going stale. Repo visibility is
+    /// enforced even on cache hits.
+    pub(crate) async fn cache_get_tagged(
+        &self,
+        key: cache::Key,
+        headers: &HeaderMap,
+    ) -> Result<cache::Lookup, Error> {
+        let Some(cache) = self.cache.as_ref().filter(|_| self.events.is_connected()) else {
+            return Ok(cache::Lookup::Miss(cache::Ticket::uncached(key)));
+        };
+        // Taken before the lookup, so that an invalidation racing with the
+        // computation of a missing response prevents caching it.
+        let generation = cache.generation().await;
+        let Some(cache::Entry {
+            body,
+            etag: Some(etag),
+        }) = cache.get(&key).await
+        else {
+            return Ok(cache::Lookup::Miss(cache::Ticket {
+                key,
+                generation: Some(generation),
+            }));
+        };
+        let ctx = self.clone();
+        let rid = key.rid;
+        blocking(move || ctx.repo(rid).map(|_| ())).await?;
+
+        if etag.matches(headers) {
+            return Ok(cache::Lookup::Hit(etag.not_modified()));
+        }
+        Ok(cache::Lookup::Hit(etag.json(body)))
+    }
+
+    /// Respond with a mutable response computed after a
+    /// [`cache::Lookup::Miss`], caching it if possible. A `None` value means
+    /// the client's copy is still current.
+    pub(crate) async fn cache_put_tagged(
+        &self,
+        ticket: cache::Ticket,
+        etag: ETag,
+        value: Option<impl serde::Serialize>,
+    ) -> Result<Response, Error> {
+        let Some(value) = value else {
+            return Ok(etag.not_modified());
+        };
+        let body = axum::body::Bytes::from(serde_json::to_vec(&value)?);
+        if let (Some(cache), Some(generation)) = (&self.cache, ticket.generation) {
+            let entry = cache::Entry::new(body.clone(), Some(etag.clone()));
+            cache.put_since(ticket.key, entry, generation).await;
+        }
+        Ok(etag.json(body))
+    }
+
+    /// Response cache usage, if the cache is enabled.
+    pub(crate) async fn cache_stats(&self) -> Option<cache::Stats> {
+        match &self.cache {
+            Some(cache) => Some(cache.stats().await),
+            None => None,
+        }
+    }
+
     /// Returns a reference to the thread-safe web configuration.
     ///
     /// Use this instead of accessing [`radicle::web::Config`] from the [`Profile`] to ensure
@@ -245,12 +463,15 @@ impl Context {
 /// directly in a handler would pin a runtime worker for the call's full
 /// duration and let a slow read stall every other request. [`Context`] is
 /// cheaply cloneable (it holds only `Arc`s), so callers move a clone into `f`.
+///
+/// Within a request, the work is bounded by the route's lane and deadline,
+/// see [`pool`].
 pub(crate) async fn blocking<T, F>(f: F) -> Result<T, Error>
 where
     F: FnOnce() -> Result<T, Error> + Send + 'static,
     T: Send + 'static,
 {
-    tokio::task::spawn_blocking(f).await?
+    pool::spawn(f).await
 }
 
 pub trait ReadCanonicalRefs {
@@ -324,8 +545,41 @@ impl ResolveTag for Repository {
     }
 }
 
+/// Compute a strong [`ETag`] for a resource derived from `repo`.
+///
+/// Every remote signs all of its refs, including its COBs, so the signed refs
+/// of all remotes, along with the top-level canonical and identity refs, pin
+/// down the repo's state. This is far cheaper than building the response.
+/// `extra` covers any other state the resource depends on. Node aliases are
+/// left out, as they're only decoration.
+#[allow(clippy::result_large_err)]
+pub(crate) fn repo_etag(repo: &Repository, extra: &[u8]) -> Result<ETag, error::Error> {
+    let mut refs = Vec::new();
+    for pattern in [
+        "refs/namespaces/*/refs/rad/sigrefs",
+        "refs/heads/*",
+        "refs/tags/*",
+        "refs/rad/*",
+    ] {
+        for r in repo.backend.references_glob(pattern)? {
+            let r = r?;
+            if let (Some(name), Some(oid)) = (r.name(), r.target()) {
+                refs.push(format!("{name} {oid}\n"));
+            }
+        }
+    }
+    refs.sort();
+
+    let mut state = format!("{RADICLE_VERSION}-{}\n", env!("GIT_HEAD")).into_bytes();
+    state.extend(refs.concat().into_bytes());
+    state.extend(extra);
+    let oid = git::raw::Oid::hash_object(git::raw::ObjectType::Blob, &state)?;
+
+    Ok(ETag::new(oid))
+}
+
 #[allow(clippy::result_large_err)]
-fn canonical_refs<R: ReadCanonicalRefs + PeelToCommit + ResolveTag>(
+pub(crate) fn canonical_refs<R: ReadCanonicalRefs + PeelToCommit + ResolveTag>(
     repo: &R,
     doc: &radicle::identity::Doc,
 ) -> Result<repo::CanonicalReferences, error::Error> {
@@ -395,7 +649,7 @@ mod search {
     use serde_json::json;
 
     use radicle::identity::doc::{Payload, PayloadId};
-    use radicle::identity::RepoId;
+    use radicle::identity::{Doc, RepoId};
     use radicle::node::routing::Store;
     use radicle::node::{AliasStore, Database};
     use radicle::profile::Aliases;
@@ -426,15 +680,32 @@ mod search {
             db: &Database,
             aliases: &Aliases,
         ) -> Option<Self> {
-            if info.doc.visibility().is_private() {
+            Self::matching(
+                q,
+                info.rid,
+                &info.doc,
+                || db.count(&info.rid).unwrap_or_default(),
+                aliases,
+            )
+        }
+
+        /// Match a repo's identity document against the query. The seed
+        /// count is only computed for matches.
+        pub fn matching(
+            q: &str,
+            rid: RepoId,
+            doc: &Doc,
+            seeds: impl FnOnce() -> usize,
+            aliases: &Aliases,
+        ) -> Option<Self> {
+            if doc.visibility().is_private() {
                 return None;
             }
-            let Ok(Some(index)) = info.doc.project().map(|p| p.name().find(q)) else {
+            let Ok(Some(index)) = doc.project().map(|p| p.name().find(q)) else {
                 return None;
             };
-            let seeds = db.count(&info.rid).unwrap_or_default();
-            let delegates = info
-                .doc
+            let seeds = seeds();
+            let delegates = doc
                 .delegates()
                 .iter()
                 .map(|did| match aliases.alias(did) {
@@ -449,8 +720,8 @@ mod search {
                 .collect::<Vec<_>>();
 
             Some(SearchResult {
-                rid: info.rid,
-                payloads: info.doc.payload().clone(),
+                rid,
+                payloads: doc.payload().clone(),
                 delegates,
                 seeds,
                 index,
@@ -494,14 +765,14 @@ mod repo {
     use radicle::identity::doc::PayloadId;
     use radicle::identity::{RepoId, Visibility};
 
-    #[derive(Default, Serialize)]
+    #[derive(Clone, Default, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct CanonicalReferences {
         pub tags: BTreeMap<RefString, Tag>,
         pub refs: BTreeMap<RefString, Oid>,
     }
 
-    #[derive(Serialize)]
+    #[derive(Clone, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Tag {
         pub commit: Oid,
@@ -511,7 +782,22 @@ mod repo {
         pub message: Option<String>,
     }
 
-    #[derive(Serialize)]
+    impl Tag {
+        /// When the tag was made: the tagger time for annotated tags, or the
+        /// commit time of the tagged commit otherwise.
+        pub fn timestamp(&self, repo: &radicle::storage::git::Repository) -> Option<i64> {
+            match &self.tagger {
+                Some(tagger) => Some(tagger.timestamp),
+                None => repo
+                    .backend
+                    .find_commit(self.commit.into())
+                    .ok()
+                    .map(|commit| commit.time().seconds()),
+            }
+        }
+    }
+
+    #[derive(Clone, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Tagger {
         pub name: String,
@@ -520,7 +806,7 @@ mod repo {
     }
 
     /// Repos info.
-    #[derive(Serialize)]
+    #[derive(Clone, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct Info {
         pub payloads: BTreeMap<PayloadId, Value>,
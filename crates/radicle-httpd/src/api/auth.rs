@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::http::{header, HeaderMap};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use radicle::crypto::Signature;
+use radicle::identity::Did;
+
+use crate::api::error::Error;
+
+/// How long a client has to sign the challenge of a new session, in seconds.
+pub const UNAUTHORIZED_SESSION_TTL: i64 = 60;
+/// How long an authorized session stays valid, in seconds.
+pub const AUTHORIZED_SESSION_TTL: i64 = 60 * 60 * 24;
+
+#[derive(Clone, Copy, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthState {
+    /// The session was created but its challenge hasn't been signed yet.
+    Unauthorized,
+    /// The challenge was signed by the session's DID.
+    Authorized,
+}
+
+/// A write API session for a single DID.
+#[derive(Clone, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub status: AuthState,
+    pub did: Did,
+    /// The exact bytes the DID's key must sign to authorize the session.
+    pub challenge: String,
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+impl Session {
+    fn is_expired(&self, now: i64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// In-memory store of write API sessions. Sessions don't survive a restart.
+#[derive(Clone, Default)]
+pub struct Sessions {
+    inner: Arc<RwLock<HashMap<Uuid, Session>>>,
+}
+
+impl Sessions {
+    /// Open an unauthorized session for `did`, returning its id.
+    pub async fn create(&self, did: Did) -> (Uuid, Session) {
+        let now = chrono::Utc::now().timestamp();
+        let id = Uuid::new_v4();
+        let session = Session {
+            status: AuthState::Unauthorized,
+            did,
+            challenge: format!("radicle-httpd:session:{id}:{}", Uuid::new_v4()),
+            issued_at: now,
+            expires_at: now + UNAUTHORIZED_SESSION_TTL,
+        };
+        let mut sessions = self.inner.write().await;
+
+        sessions.retain(|_, s| !s.is_expired(now));
+        sessions.insert(id, session.clone());
+
+        (id, session)
+    }
+
+    /// Get a session that hasn't expired yet.
+    pub async fn get(&self, id: &Uuid) -> Option<Session> {
+        let now = chrono::Utc::now().timestamp();
+
+        self.inner
+            .read()
+            .await
+            .get(id)
+            .filter(|s| !s.is_expired(now))
+            .cloned()
+    }
+
+    /// Authorize a session by checking `signature` against its challenge.
+    pub async fn authorize(&self, id: &Uuid, signature: &Signature) -> Result<Session, Error> {
+        let now = chrono::Utc::now().timestamp();
+        let mut sessions = self.inner.write().await;
+        let session = sessions
+            .get_mut(id)
+            .filter(|s| !s.is_expired(now))
+            .ok_or(Error::NotFound)?;
+
+        if session.status == AuthState::Authorized {
+            return Ok(session.clone());
+        }
+        session
+            .did
+            .as_key()
+            .verify(session.challenge.as_bytes(), signature)
+            .map_err(|_| Error::Unauthorized)?;
+        session.status = AuthState::Authorized;
+        session.expires_at = now + AUTHORIZED_SESSION_TTL;
+
+        Ok(session.clone())
+    }
+
+    /// Remove a session. Returns whether it existed.
+    pub async fn remove(&self, id: &Uuid) -> bool {
+        self.inner.write().await.remove(id).is_some()
+    }
+
+    /// Resolve the authorized session referenced by the request's
+    /// `Authorization: Bearer <session-id>` header.
+    pub async fn authenticate(&self, headers: &HeaderMap) -> Result<Session, Error> {
+        let id = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| token.trim().parse::<Uuid>().ok())
+            .ok_or(Error::Unauthorized)?;
+
+        match self.get(&id).await {
+            Some(session) if session.status == AuthState::Authorized => Ok(session),
+            _ => Err(Error::Unauthorized),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use radicle::crypto::{Seed, Signer, SigningKey};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn sessions_are_authorized_by_signed_challenge() {
+        let signer = SigningKey::from_seed(Seed::new([0xff; 32]));
+        let other = SigningKey::from_seed(Seed::new([0xee; 32]));
+        let sessions = Sessions::default();
+        let (id, session) = sessions.create(Did::from(*signer.public_key())).await;
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {id}")).unwrap(),
+        );
+        assert!(matches!(
+            sessions.authenticate(&headers).await,
+            Err(Error::Unauthorized)
+        ));
+
+        let forged = other.sign(session.challenge.as_bytes());
+        assert!(matches!(
+            sessions.authorize(&id, &forged).await,
+            Err(Error::Unauthorized)
+        ));
+
+        let signature = signer.sign(session.challenge.as_bytes());
+        let session = sessions.authorize(&id, &signature).await.unwrap();
+        assert_eq!(session.status, AuthState::Authorized);
+        assert_eq!(
+            sessions.authenticate(&headers).await.unwrap().did,
+            session.did
+        );
+
+        assert!(sessions.remove(&id).await);
+        assert!(sessions.authenticate(&headers).await.is_err());
+    }
+}
@@ -0,0 +1,331 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{middleware, Router};
+
+use radicle::issue::cache::Issues as _;
+use radicle::node::routing::Store as _;
+use radicle::patch::cache::Patches as _;
+use radicle::storage::ReadRepository;
+
+use crate::api::error::Error;
+use crate::api::xml::escape;
+use crate::api::{pool, Context};
+use crate::axum_extra::Path;
+
+/// How long clients and proxies may cache a badge. Badges reflect mutable
+/// repository state, so this is kept short.
+const MAX_AGE_SECONDS: u64 = 300;
+
+/// Approximate advance width of a character in 11px Verdana, which is what
+/// badge renderers conventionally use to size their boxes.
+const CHAR_WIDTH: usize = 7;
+/// Horizontal padding on either side of a badge label or message.
+const PADDING: usize = 6;
+
+const BLUE: &str = "#007ec6";
+const GREEN: &str = "#4c1";
+const YELLOW: &str = "#dfb317";
+const RED: &str = "#e05d44";
+const GREY: &str = "#9f9f9f";
+
+pub fn router(ctx: Context) -> Router {
+    let limits = ctx.pool().clone();
+
+    Router::new()
+        .route("/{rid}/seeds.svg", get(seeds_handler))
+        .route("/{rid}/issues.svg", get(issues_handler))
+        .route("/{rid}/patches.svg", get(patches_handler))
+        .route("/{rid}/tag.svg", get(tag_handler))
+        .route("/{rid}/ci.svg", get(ci_handler))
+        // Badges aren't served under `/api/v1`, which sets the lane for its routes.
+        .layer(middleware::from_fn_with_state(limits, pool::light))
+        .with_state(ctx)
+}
+
+/// A two-part badge, e.g. `issues | 3 open`.
+#[derive(Debug, PartialEq, Eq)]
+struct Badge {
+    label: &'static str,
+    message: String,
+    color: &'static str,
+}
+
+impl Badge {
+    fn new(label: &'static str, message: impl ToString, color: &'static str) -> Self {
+        Self {
+            label,
+            message: message.to_string(),
+            color,
+        }
+    }
+
+    /// Render the badge as an SVG document.
+    fn render(&self) -> String {
+        let label_width = text_width(self.label);
+        let message_width = text_width(&self.message);
+        let width = label_width + message_width;
+        let label = escape(self.label);
+        let message = escape(&self.message);
+        let label_x = label_width / 2;
+        let message_x = label_width + message_width / 2;
+
+        format!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}"><title>{label}: {message}</title><linearGradient id="s" x2="0" y2="100%"><stop offset="0" stop-color="#bbb" stop-opacity=".1"/><stop offset="1" stop-opacity=".1"/></linearGradient><clipPath id="r"><rect width="{width}" height="20" rx="3" fill="#fff"/></clipPath><g clip-path="url(#r)"><rect width="{label_width}" height="20" fill="#555"/><rect x="{label_width}" width="{message_width}" height="20" fill="{color}"/><rect width="{width}" height="20" fill="url(#s)"/></g><g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11"><text x="{label_x}" y="15" fill="#010101" fill-opacity=".3">{label}</text><text x="{label_x}" y="14">{label}</text><text x="{message_x}" y="15" fill="#010101" fill-opacity=".3">{message}</text><text x="{message_x}" y="14">{message}</text></g></svg>"##,
+            color = self.color,
+        )
+    }
+}
+
+impl IntoResponse for Badge {
+    fn into_response(self) -> axum::response::Response {
+        (
+            [
+                (header::CONTENT_TYPE, "image/svg+xml".to_owned()),
+                (
+                    header::CACHE_CONTROL,
+                    format!("public, max-age={MAX_AGE_SECONDS}, must-revalidate"),
+                ),
+            ],
+            self.render(),
+        )
+            .into_response()
+    }
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count() * CHAR_WIDTH + 2 * PADDING
+}
+
+/// Number of nodes seeding the repo.
+/// `GET /badges/:rid/seeds.svg`
+async fn seeds_handler(State(ctx): State<Context>, Path(rid): Path<String>) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let seeds = crate::api::blocking(move || {
+        ctx.repo(rid)?;
+        let db = ctx.profile.database()?;
+
+        Ok::<_, Error>(db.count(&rid).unwrap_or_default())
+    })
+    .await?;
+
+    Ok::<_, Error>(Badge::new("seeds", seeds, BLUE))
+}
+
+/// Number of open issues.
+/// `GET /badges/:rid/issues.svg`
+async fn issues_handler(State(ctx): State<Context>, Path(rid): Path<String>) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let open = crate::api::blocking(move || {
+        let (repo, _) = ctx.repo(rid)?;
+        let counts = ctx.profile.issues(&repo)?.counts()?;
+
+        Ok::<_, Error>(counts.open)
+    })
+    .await?;
+    let color = if open == 0 { GREEN } else { YELLOW };
+
+    Ok::<_, Error>(Badge::new("issues", format!("{open} open"), color))
+}
+
+/// Number of open patches.
+/// `GET /badges/:rid/patches.svg`
+async fn patches_handler(State(ctx): State<Context>, Path(rid): Path<String>) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let open = crate::api::blocking(move || {
+        let (repo, _) = ctx.repo(rid)?;
+        let counts = ctx.profile.patches(&repo)?.counts()?;
+
+        Ok::<_, Error>(counts.open)
+    })
+    .await?;
+
+    Ok::<_, Error>(Badge::new("patches", format!("{open} open"), BLUE))
+}
+
+/// The most recent canonical tag, by tagger time for annotated tags and by
+/// commit time otherwise.
+/// `GET /badges/:rid/tag.svg`
+async fn tag_handler(State(ctx): State<Context>, Path(rid): Path<String>) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let latest = crate::api::blocking(move || {
+        let (repo, doc) = ctx.repo(rid)?;
+        let refs = crate::api::canonical_refs(&repo, &doc.doc)?;
+        let latest = refs
+            .tags
+            .into_iter()
+            .filter_map(|(refname, tag)| {
+                let time = tag.timestamp(&repo)?;
+                let name = refname.as_str().strip_prefix("refs/tags/")?.to_owned();
+                Some((time, name))
+            })
+            .max();
+
+        Ok::<_, Error>(latest.map(|(_, name)| name))
+    })
+    .await?;
+
+    Ok::<_, Error>(match latest {
+        Some(name) => Badge::new("tag", name, BLUE),
+        None => Badge::new("tag", "none", GREY),
+    })
+}
+
+/// The aggregate state of all CI runs on a commit.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CiState {
+    running: bool,
+    failed: bool,
+    succeeded: bool,
+}
+
+impl CiState {
+    fn badge(&self) -> Badge {
+        match self {
+            Self { running: true, .. } => Badge::new("ci", "running", YELLOW),
+            Self { failed: true, .. } => Badge::new("ci", "failing", RED),
+            Self {
+                succeeded: true, ..
+            } => Badge::new("ci", "passing", GREEN),
+            _ => Badge::new("ci", "unknown", GREY),
+        }
+    }
+}
+
+/// CI status of the repo's canonical head.
+/// `GET /badges/:rid/ci.svg`
+async fn ci_handler(State(ctx): State<Context>, Path(rid): Path<String>) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let state = crate::api::blocking(move || {
+        let (repo, _) = ctx.repo(rid)?;
+        let (_, head) = repo.head()?;
+        let store = radicle_job::Jobs::open(&repo, radicle::cob::store::access::ReadOnly)?;
+
+        let mut state = CiState::default();
+        for result in radicle_job::Jobs::find_by_commit(&store, head)? {
+            let (_, job) = result?;
+            for (_, runs) in job.runs().iter() {
+                for (_, run) in runs.iter() {
+                    match run.status() {
+                        radicle_job::Status::Started => state.running = true,
+                        radicle_job::Status::Finished(radicle_job::Reason::Failed) => {
+                            state.failed = true
+                        }
+                        radicle_job::Status::Finished(radicle_job::Reason::Succeeded) => {
+                            state.succeeded = true
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok::<_, Error>(state)
+    })
+    .await?;
+
+    Ok::<_, Error>(state.badge())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_text_is_escaped() {
+        let svg = Badge::new("tag", "<v1&2>", BLUE).render();
+
+        assert!(svg.contains("&lt;v1&amp;2&gt;"));
+        assert!(!svg.contains("<v1&2>"));
+    }
+
+    #[test]
+    fn ci_state_prefers_running_then_failing() {
+        let state = CiState {
+            running: true,
+            failed: true,
+            succeeded: true,
+        };
+        assert_eq!(state.badge().message, "running");
+
+        let state = CiState {
+            failed: true,
+            succeeded: true,
+            ..CiState::default()
+        };
+        assert_eq!(state.badge().message, "failing");
+
+        assert_eq!(CiState::default().badge().message, "unknown");
+    }
+}
+
+#[cfg(test)]
+mod routes {
+    use std::collections::HashMap;
+
+    use axum::http::StatusCode;
+
+    use crate::test::{self, get, RID, RID_PRIVATE};
+
+    #[tokio::test]
+    async fn test_badges() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+
+        let response = get(&app, format!("/{RID}/issues.svg")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "image/svg+xml"
+        );
+        assert_eq!(
+            response.headers().get("Cache-Control").unwrap(),
+            "public, max-age=300, must-revalidate"
+        );
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains("issues: 1 open"));
+
+        let response = get(&app, format!("/{RID}/patches.svg")).await;
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains("patches: 1 open"));
+
+        let response = get(&app, format!("/{RID}/seeds.svg")).await;
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains("seeds: 1"));
+
+        let response = get(&app, format!("/{RID}/tag.svg")).await;
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains("tag: none"));
+
+        let response = get(&app, format!("/{RID}/ci.svg")).await;
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains("ci: unknown"));
+
+        let response = get(&app, format!("/{RID_PRIVATE}/issues.svg")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_badges_tag() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed_multi_peer(tmp.path()));
+
+        let response = get(&app, format!("/{RID}/tag.svg")).await;
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains("tag: v1.0"));
+    }
+
+    #[tokio::test]
+    async fn test_badges_alias() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut ctx = test::seed(tmp.path());
+        ctx.set_repo_aliases(HashMap::from([("hello".to_owned(), RID.parse().unwrap())]));
+        let app = super::router(ctx);
+
+        let response = get(&app, "/hello/issues.svg").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = get(&app, "/nope/issues.svg").await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,4 +1,6 @@
-use axum::http::StatusCode;
+use std::time::Duration;
+
+use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde_json::json;
@@ -12,6 +14,18 @@ pub enum Error {
     #[error("entity not found")]
     NotFound,
 
+    /// The request lacks a valid, authorized session.
+    #[error("unauthorized")]
+    Unauthorized,
+
+    /// The session's identity isn't allowed to perform the request.
+    #[error("{0}")]
+    Forbidden(&'static str),
+
+    /// The server is too busy to handle the request before its deadline.
+    #[error("server is overloaded, try again later")]
+    Overloaded { retry_after: Duration },
+
     /// A blocking task failed to complete.
     #[error(transparent)]
     Task(#[from] tokio::task::JoinError),
@@ -103,13 +117,24 @@ pub enum Error {
     /// Node error.
     #[error(transparent)]
     Node(#[from] radicle::node::Error),
+
+    /// JSON serialization error.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
 }
 
 impl IntoResponse for Error {
     fn into_response(self) -> Response {
         let message = self.to_string();
+        let retry_after = match &self {
+            Error::Overloaded { retry_after } => Some(*retry_after),
+            _ => None,
+        };
         let (status, msg) = match self {
             Error::NotFound => (StatusCode::NOT_FOUND, None),
+            Error::Unauthorized => (StatusCode::UNAUTHORIZED, None),
+            Error::Forbidden(msg) => (StatusCode::FORBIDDEN, Some(msg.to_owned())),
+            Error::Overloaded { .. } => (StatusCode::SERVICE_UNAVAILABLE, Some(message)),
             Error::CobStore(e @ radicle::cob::store::Error::NotFound(_, _)) => {
                 (StatusCode::NOT_FOUND, Some(e.to_string()))
             }
@@ -153,7 +178,14 @@ impl IntoResponse for Error {
             "code": status.as_u16()
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let Some(retry_after) = retry_after {
+            response.headers_mut().insert(
+                header::RETRY_AFTER,
+                HeaderValue::from(retry_after.as_secs()),
+            );
+        }
+        response
     }
 }
 
@@ -166,4 +198,14 @@ mod tests {
         let response = Error::NotFound.into_response();
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    #[test]
+    fn overloaded_maps_to_503_with_retry_after() {
+        let response = Error::Overloaded {
+            retry_after: Duration::from_secs(5),
+        }
+        .into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "5");
+    }
 }
@@ -0,0 +1,270 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::Value;
+use tokio::sync::{broadcast, watch};
+
+use radicle::identity::RepoId;
+use radicle::node::{Event, Handle as _};
+use radicle::storage::{ReadRepository, ReadStorage};
+use radicle::Profile;
+
+/// Number of recent events kept for clients resuming with `Last-Event-ID`.
+pub const BACKLOG_SIZE: usize = 256;
+/// How long to wait before re-subscribing after the node connection is lost.
+pub const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// A node event relayed to API clients.
+#[derive(Debug)]
+pub struct Envelope {
+    /// Sequence number, sent as the SSE event id.
+    pub id: u64,
+    /// The repo the event is about.
+    pub rid: RepoId,
+    /// The event type, e.g. `refsFetched`.
+    pub kind: String,
+    /// The event as serialized by the node.
+    pub data: Value,
+}
+
+struct Backlog {
+    /// Id of the last published event.
+    last: u64,
+    events: VecDeque<Arc<Envelope>>,
+}
+
+/// Fan-out of public repo events from the node to any number of API clients.
+///
+/// Event ids start at the server's start time in milliseconds, so they keep
+/// increasing across restarts. A `Last-Event-ID` from before a restart is
+/// older than every new id, so it replays the whole backlog. Events that
+/// dropped out of the backlog are lost without notice.
+#[derive(Clone)]
+pub struct Events {
+    tx: broadcast::Sender<Arc<Envelope>>,
+    backlog: Arc<Mutex<Backlog>>,
+    /// Whether we're currently subscribed to the node's events.
+    connected: Arc<watch::Sender<bool>>,
+    /// Repos whose events weren't relayed, because they aren't public.
+    withheld: broadcast::Sender<RepoId>,
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        let (tx, _) = broadcast::channel(BACKLOG_SIZE);
+        let (withheld, _) = broadcast::channel(BACKLOG_SIZE);
+        let backlog = Backlog {
+            last: chrono::Utc::now().timestamp_millis() as u64,
+            events: VecDeque::with_capacity(BACKLOG_SIZE),
+        };
+
+        Self {
+            tx,
+            backlog: Arc::new(Mutex::new(backlog)),
+            connected: Arc::new(watch::Sender::new(false)),
+            withheld,
+        }
+    }
+}
+
+impl Events {
+    /// Publish an event about `rid`. The caller is responsible for only
+    /// publishing events about public repos.
+    pub fn publish(&self, rid: RepoId, data: Value) -> Arc<Envelope> {
+        let kind = data
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or("event")
+            .to_owned();
+        let mut backlog = self.backlog.lock().unwrap_or_else(|e| e.into_inner());
+
+        backlog.last += 1;
+        let envelope = Arc::new(Envelope {
+            id: backlog.last,
+            rid,
+            kind,
+            data,
+        });
+        if backlog.events.len() == BACKLOG_SIZE {
+            backlog.events.pop_front();
+        }
+        backlog.events.push_back(envelope.clone());
+        // Sending fails only when nobody is listening.
+        self.tx.send(envelope.clone()).ok();
+
+        envelope
+    }
+
+    /// Subscribe to new events. Returns the backlogged events published after
+    /// `last_id`, if any, along with a receiver for everything after them.
+    pub fn subscribe(
+        &self,
+        last_id: Option<u64>,
+    ) -> (Vec<Arc<Envelope>>, broadcast::Receiver<Arc<Envelope>>) {
+        // Holding the lock while subscribing ensures no event is either
+        // missed or delivered twice.
+        let backlog = self.backlog.lock().unwrap_or_else(|e| e.into_inner());
+        let rx = self.tx.subscribe();
+        let replay = match last_id {
+            Some(last_id) => backlog
+                .events
+                .iter()
+                .filter(|e| e.id > last_id)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        (replay, rx)
+    }
+
+    /// Whether events are currently relayed from the node. While they aren't,
+    /// changes to repos go unnoticed.
+    pub fn is_connected(&self) -> bool {
+        *self.connected.borrow()
+    }
+
+    /// Watch the connection to the node's event stream. Events may have been
+    /// missed whenever it changes.
+    pub fn connection(&self) -> watch::Receiver<bool> {
+        self.connected.subscribe()
+    }
+
+    /// Note that an event about `rid` wasn't relayed, because the repo isn't
+    /// public. It may have just turned private, so anything derived from it
+    /// must be dropped.
+    pub fn withhold(&self, rid: RepoId) {
+        // Sending fails only when nobody is listening.
+        self.withheld.send(rid).ok();
+    }
+
+    /// Watch for repos whose events are withheld.
+    pub fn withheld(&self) -> broadcast::Receiver<RepoId> {
+        self.withheld.subscribe()
+    }
+
+    #[cfg(test)]
+    pub fn set_connected(&self, connected: bool) {
+        self.connected.send_replace(connected);
+    }
+
+    /// Relay public repo events from the node until the process exits,
+    /// re-subscribing whenever the node connection is lost.
+    pub async fn listen(self, profile: Arc<Profile>) {
+        loop {
+            let events = self.clone();
+            let profile = profile.clone();
+            let result = tokio::task::spawn_blocking(move || events.relay(&profile)).await;
+            self.connected.send_replace(false);
+
+            match result {
+                Ok(Ok(())) => tracing::debug!("Node event stream ended, reconnecting"),
+                Ok(Err(e)) => tracing::debug!("Node event stream error: {e}, reconnecting"),
+                Err(e) => tracing::error!("Node event relay panicked: {e}"),
+            }
+            tokio::time::sleep(RECONNECT_BACKOFF).await;
+        }
+    }
+
+    fn relay(&self, profile: &Profile) -> Result<(), radicle::node::Error> {
+        let node = radicle::Node::new(profile.socket_from_env());
+        let events = node.subscribe(Duration::from_secs(1))?;
+
+        tracing::info!("Subscribed to node events");
+        self.connected.send_replace(true);
+
+        for event in events {
+            let event = match event {
+                Ok(event) => event,
+                Err(radicle::node::Error::TimedOut) => continue,
+                Err(e) => return Err(e),
+            };
+            let Some(rid) = event_rid(&event) else {
+                continue;
+            };
+            if !is_public(profile, rid) {
+                self.withhold(rid);
+                continue;
+            }
+            match serde_json::to_value(&event) {
+                Ok(data) => {
+                    self.publish(rid, data);
+                }
+                Err(e) => tracing::warn!("Failed to serialize node event: {e}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The repo a node event is about, for the events relayed to clients.
+fn event_rid(event: &Event) -> Option<RepoId> {
+    match event {
+        Event::RefsFetched { rid, .. }
+        | Event::RefsSynced { rid, .. }
+        | Event::RefsAnnounced { rid, .. }
+        | Event::LocalRefsAnnounced { rid, .. }
+        | Event::CanonicalRefUpdated { rid, .. }
+        | Event::SeedDiscovered { rid, .. }
+        | Event::SeedDropped { rid, .. } => Some(*rid),
+        _ => None,
+    }
+}
+
+/// Whether `rid` is stored locally and public. Events about repos we don't
+/// have, or can't show, are never relayed.
+fn is_public(profile: &Profile, rid: RepoId) -> bool {
+    profile
+        .storage
+        .repository(rid)
+        .ok()
+        .and_then(|repo| repo.identity_doc().ok())
+        .is_some_and(|doc| doc.visibility().is_public())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn backlog_is_replayed_after_last_id() {
+        let rid = RepoId::from_str("rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp").unwrap();
+        let events = Events::default();
+        let first = events.publish(rid, json!({ "type": "refsFetched" }));
+        let second = events.publish(rid, json!({ "type": "seedDiscovered" }));
+
+        assert_eq!(second.id, first.id + 1);
+        assert_eq!(second.kind, "seedDiscovered");
+
+        let (replay, _) = events.subscribe(None);
+        assert!(replay.is_empty());
+
+        let (replay, mut rx) = events.subscribe(Some(first.id));
+        assert_eq!(
+            replay.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![second.id]
+        );
+
+        let third = events.publish(rid, json!({ "type": "refsSynced" }));
+        assert_eq!(rx.try_recv().unwrap().id, third.id);
+    }
+
+    #[test]
+    fn backlog_is_bounded() {
+        let rid = RepoId::from_str("rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp").unwrap();
+        let events = Events::default();
+        let first = events.publish(rid, json!({}));
+
+        for _ in 0..BACKLOG_SIZE {
+            events.publish(rid, json!({}));
+        }
+        let (replay, _) = events.subscribe(Some(0));
+        assert_eq!(replay.len(), BACKLOG_SIZE);
+        assert_eq!(replay[0].id, first.id + 1);
+    }
+}
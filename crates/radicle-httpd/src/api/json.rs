@@ -55,6 +55,37 @@ pub fn embeds(embeds: &[cob::Embed<cob::Uri>]) -> Vec<Value> {
         .collect::<Vec<_>>()
 }
 
+/// Give every embed in `value` that references a git blob a `url` under
+/// `/raw/:rid/embeds/:cob/:oid`, where clients can fetch its content.
+pub fn link_embeds(value: &mut Value, rid: &identity::RepoId, cob: &cob::ObjectId) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(embeds)) = map.get_mut("embeds") {
+                for embed in embeds.iter_mut().filter_map(Value::as_object_mut) {
+                    let Some(oid) = embed
+                        .get("content")
+                        .and_then(Value::as_str)
+                        .and_then(|content| content.strip_prefix("git:"))
+                    else {
+                        continue;
+                    };
+                    let url = format!("/raw/{rid}/embeds/{cob}/{oid}");
+                    embed.insert("url".to_owned(), Value::String(url));
+                }
+            }
+            for value in map.values_mut() {
+                link_embeds(value, rid, cob);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                link_embeds(value, rid, cob);
+            }
+        }
+        _ => {}
+    }
+}
+
 /// Returns JSON for an `Edit`.
 pub fn edit(edit: &cob::thread::Edit, aliases: &impl AliasStore) -> Value {
     json!({
@@ -79,3 +110,34 @@ impl<'a> Author<'a> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embeds_are_linked() {
+        let rid = "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp".parse().unwrap();
+        let cob = "ca67d195c0b308b51810dedd93157a20764d5db5".parse().unwrap();
+        let oid = "e8c676b9e3b42308dc9d218b70faa5408f8e58ca";
+        let mut value = json!({
+            "discussion": [{
+                "embeds": [
+                    { "name": "screenshot.png", "content": format!("git:{oid}") },
+                    { "name": "elsewhere", "content": "https://example.com" },
+                ],
+                "edits": [{
+                    "embeds": [{ "name": "screenshot.png", "content": format!("git:{oid}") }],
+                }],
+            }],
+        });
+
+        link_embeds(&mut value, &rid, &cob);
+
+        let url = format!("/raw/{rid}/embeds/{cob}/{oid}");
+        let comment = &value["discussion"][0];
+        assert_eq!(comment["embeds"][0]["url"], url.as_str());
+        assert_eq!(comment["embeds"][1].get("url"), None);
+        assert_eq!(comment["edits"][0]["embeds"][0]["url"], url.as_str());
+    }
+}
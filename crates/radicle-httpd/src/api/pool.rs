@@ -0,0 +1,207 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use tokio::sync::Semaphore;
+use tokio::time::Instant;
+
+use crate::api::error::Error;
+use crate::BlockingOptions;
+
+/// How long a request to a cheap endpoint, like node info or repo listings,
+/// may wait for and run blocking work.
+pub const LIGHT_DEADLINE: Duration = Duration::from_secs(10);
+/// How long a request to an endpoint that walks trees, diffs or history may
+/// wait for and run blocking work.
+pub const HEAVY_DEADLINE: Duration = Duration::from_secs(30);
+/// Suggested delay before retrying a request that was turned away.
+pub const RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// The kind of work a route does. Each lane has its own permits and queue,
+/// so a burst of expensive requests can't starve cheap ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lane {
+    Light,
+    Heavy,
+}
+
+struct Limit {
+    permits: Arc<Semaphore>,
+    /// Blocking calls currently waiting for a permit.
+    queued: AtomicUsize,
+    /// How many calls may wait for a permit before new ones are turned away.
+    depth: usize,
+}
+
+impl Limit {
+    fn new(options: &BlockingOptions) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(options.limit.get())),
+            queued: AtomicUsize::new(0),
+            depth: options.queue,
+        }
+    }
+
+    /// Take a place in the queue, if there's one left.
+    fn enqueue(&self) -> Option<Queued<'_>> {
+        self.queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| {
+                (n < self.depth).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Queued(&self.queued))
+    }
+}
+
+/// A place in a lane's queue, given up when dropped.
+struct Queued<'a>(&'a AtomicUsize);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Bounds the blocking work done on behalf of requests.
+#[derive(Clone)]
+pub struct Pool {
+    light: Arc<Limit>,
+    heavy: Arc<Limit>,
+}
+
+impl Pool {
+    /// Create a pool where each lane runs at most `options.limit` calls at
+    /// once, with at most `options.queue` more waiting.
+    pub fn new(options: &BlockingOptions) -> Self {
+        Self {
+            light: Arc::new(Limit::new(options)),
+            heavy: Arc::new(Limit::new(options)),
+        }
+    }
+
+    fn limit(&self, lane: Lane) -> &Limit {
+        match lane {
+            Lane::Light => &self.light,
+            Lane::Heavy => &self.heavy,
+        }
+    }
+}
+
+/// The lane and deadline of the request being handled.
+#[derive(Clone)]
+struct Scope {
+    pool: Pool,
+    lane: Lane,
+    deadline: Instant,
+}
+
+tokio::task_local! {
+    static SCOPE: Scope;
+}
+
+/// Route middleware putting a request's blocking work in the light lane.
+pub async fn light(State(pool): State<Pool>, request: Request, next: Next) -> Response {
+    scoped(pool, Lane::Light, LIGHT_DEADLINE, request, next).await
+}
+
+/// Route middleware putting a request's blocking work in the heavy lane.
+pub async fn heavy(State(pool): State<Pool>, request: Request, next: Next) -> Response {
+    scoped(pool, Lane::Heavy, HEAVY_DEADLINE, request, next).await
+}
+
+async fn scoped(
+    pool: Pool,
+    lane: Lane,
+    deadline: Duration,
+    request: Request,
+    next: Next,
+) -> Response {
+    let scope = Scope {
+        pool,
+        lane,
+        deadline: Instant::now() + deadline,
+    };
+    SCOPE.scope(scope, next.run(request)).await
+}
+
+/// Run `f` on the blocking thread pool, within the limits of the current
+/// request's lane. When no permit frees up before the request's deadline, or
+/// `f` doesn't finish by then, the request fails with [`Error::Overloaded`].
+///
+/// Outside of a request, e.g. in background tasks, `f` runs unbounded.
+pub async fn spawn<T, F>(f: F) -> Result<T, Error>
+where
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+    T: Send + 'static,
+{
+    let Ok(scope) = SCOPE.try_with(Scope::clone) else {
+        return tokio::task::spawn_blocking(f).await?;
+    };
+    let overloaded = || Error::Overloaded {
+        retry_after: RETRY_AFTER,
+    };
+    let limit = scope.pool.limit(scope.lane);
+    let queued = limit.enqueue().ok_or_else(overloaded)?;
+    let permit = tokio::time::timeout_at(scope.deadline, limit.permits.clone().acquire_owned())
+        .await
+        .map_err(|_| overloaded())?
+        .map_err(|_| overloaded())?;
+    drop(queued);
+
+    // The permit moves into the task, so that work outliving its request's
+    // deadline still counts against the limit.
+    let task = tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        f()
+    });
+    tokio::time::timeout_at(scope.deadline, task)
+        .await
+        .map_err(|_| overloaded())??
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn lanes_are_bounded_separately() {
+        let pool = Pool::new(&BlockingOptions {
+            limit: NonZeroUsize::MIN,
+            queue: 0,
+        });
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let scope = |lane| Scope {
+            pool: pool.clone(),
+            lane,
+            deadline: Instant::now() + Duration::from_secs(10),
+        };
+
+        // Occupy the only heavy permit until the channel is closed.
+        let busy = tokio::spawn(SCOPE.scope(
+            scope(Lane::Heavy),
+            spawn(move || {
+                rx.recv().ok();
+                Ok(())
+            }),
+        ));
+        while pool.heavy.permits.available_permits() > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let result = SCOPE.scope(scope(Lane::Heavy), spawn(|| Ok(()))).await;
+        assert!(matches!(result, Err(Error::Overloaded { .. })));
+
+        let result = SCOPE.scope(scope(Lane::Light), spawn(|| Ok(1))).await;
+        assert_eq!(result.unwrap(), 1);
+
+        drop(tx);
+        busy.await.unwrap().unwrap();
+        let result = SCOPE.scope(scope(Lane::Heavy), spawn(|| Ok(2))).await;
+        assert_eq!(result.unwrap(), 2);
+    }
+}
@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use radicle::identity::{Did, Doc, RepoId};
+use radicle::storage::{ReadRepository as _, ReadStorage as _};
+use radicle_surf::Repository;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api::error::Error;
+use crate::api::{repo, Context};
+use crate::cache::INVALIDATING_EVENTS;
+
+/// How often every summary is recomputed, to pick up changes the node
+/// doesn't report, like updated node aliases.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// What repo listings need to know about a public repo, computed ahead of
+/// time so that listing doesn't open every repo on every request.
+pub struct Summary {
+    /// The repo info, as served by listings.
+    pub info: repo::Info,
+    pub doc: Doc,
+    /// Committer time of the canonical head, for sorting by activity.
+    pub head_time: Option<i64>,
+}
+
+impl Summary {
+    pub fn is_delegate(&self, did: &Did) -> bool {
+        self.doc.delegates().iter().any(|d| d == did)
+    }
+}
+
+/// Summaries of all public repos in storage, refreshed in the background on
+/// node events and periodically. Until the first refresh completes, callers
+/// compute what they need from storage instead.
+#[derive(Clone, Default)]
+pub struct Summaries {
+    table: Arc<RwLock<BTreeMap<RepoId, Arc<Summary>>>>,
+    ready: Arc<AtomicBool>,
+}
+
+impl Summaries {
+    /// Whether the summaries were computed at least once.
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// All summaries, ordered by RID, or `None` if they aren't computed yet.
+    pub fn all(&self) -> Option<Vec<Arc<Summary>>> {
+        if !self.is_ready() {
+            return None;
+        }
+        let table = self.table.read().unwrap_or_else(|e| e.into_inner());
+
+        Some(table.values().cloned().collect())
+    }
+
+    /// The summary of a public repo, if it's computed.
+    pub fn get(&self, rid: &RepoId) -> Option<Arc<Summary>> {
+        self.table
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(rid)
+            .cloned()
+    }
+
+    /// The info of a public repo, from its summary if there's one, or else
+    /// computed from storage.
+    pub fn info(&self, ctx: &Context, rid: RepoId) -> Option<repo::Info> {
+        if let Some(summary) = self.get(&rid) {
+            return Some(summary.info.clone());
+        }
+        let (repo, doc) = ctx.repo(rid).ok()?;
+        ctx.repo_info(&repo, doc).ok()
+    }
+
+    /// Recompute the summary of `rid`, dropping it if the repo is gone or
+    /// not public anymore.
+    pub fn refresh(&self, ctx: &Context, rid: RepoId) {
+        let summary = summarize(ctx, rid)
+            .inspect_err(|e| tracing::debug!("Not summarizing {rid}: {e}"))
+            .ok();
+        let mut table = self.table.write().unwrap_or_else(|e| e.into_inner());
+
+        match summary {
+            Some(summary) => table.insert(rid, Arc::new(summary)),
+            None => table.remove(&rid),
+        };
+    }
+
+    /// Recompute every summary.
+    #[allow(clippy::result_large_err)]
+    pub fn refresh_all(&self, ctx: &Context) -> Result<(), Error> {
+        let mut table = BTreeMap::new();
+
+        for info in ctx.profile.storage.repositories()? {
+            if !info.doc.visibility().is_public() {
+                continue;
+            }
+            match summarize(ctx, info.rid) {
+                Ok(summary) => {
+                    table.insert(info.rid, Arc::new(summary));
+                }
+                Err(e) => tracing::debug!("Not summarizing {}: {e}", info.rid),
+            }
+        }
+        *self.table.write().unwrap_or_else(|e| e.into_inner()) = table;
+        self.ready.store(true, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Keep the summaries up to date until the process exits.
+    pub async fn run(self, ctx: Context) {
+        let events = ctx.events().clone();
+        let (_, mut rx) = events.subscribe(None);
+        let mut withheld = events.withheld();
+        let mut connection = events.connection();
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+
+        loop {
+            let rid = tokio::select! {
+                _ = interval.tick() => None,
+                result = rx.recv() => match result {
+                    Ok(envelope) if INVALIDATING_EVENTS.contains(&envelope.kind.as_str()) => {
+                        Some(envelope.rid)
+                    }
+                    Ok(_) => continue,
+                    // Some events were missed.
+                    Err(RecvError::Lagged(_)) => None,
+                    Err(RecvError::Closed) => break,
+                },
+                // The repo may have turned private, and must stop being listed.
+                result = withheld.recv() => match result {
+                    Ok(rid) => Some(rid),
+                    Err(RecvError::Lagged(_)) => None,
+                    Err(RecvError::Closed) => break,
+                },
+                result = connection.changed() => {
+                    if result.is_err() {
+                        break;
+                    }
+                    None
+                }
+            };
+            let summaries = self.clone();
+            let ctx = ctx.clone();
+            let result = tokio::task::spawn_blocking(move || match rid {
+                Some(rid) => {
+                    summaries.refresh(&ctx, rid);
+                    Ok(())
+                }
+                None => summaries.refresh_all(&ctx),
+            })
+            .await;
+
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => tracing::warn!("Failed to refresh repo summaries: {e}"),
+                Err(e) => tracing::error!("Repo summary refresh panicked: {e}"),
+            }
+        }
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn summarize(ctx: &Context, rid: RepoId) -> Result<Summary, Error> {
+    let (repo, doc) = ctx.repo(rid)?;
+    let head_time = Repository::open(repo.path()).ok().and_then(|surf| {
+        let head = surf.head().ok()?;
+        let commit = surf.commit(head).ok()?;
+
+        Some(commit.committer.time.seconds())
+    });
+    let summary_doc = doc.doc.clone();
+    let info = ctx.repo_info(&repo, doc)?;
+
+    Ok(Summary {
+        info,
+        doc: summary_doc,
+        head_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test::{self, RID, RID_PRIVATE};
+
+    #[test]
+    fn summaries_cover_public_repos() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let summaries = ctx.summaries();
+
+        assert!(summaries.all().is_none());
+        summaries.refresh_all(&ctx).unwrap();
+
+        let all = summaries.all().unwrap();
+        assert!(all.iter().any(|s| s.info.rid.to_string() == RID));
+        assert!(all.iter().all(|s| s.info.rid.to_string() != RID_PRIVATE));
+
+        let rid = RID.parse().unwrap();
+        let summary = summaries.get(&rid).unwrap();
+        assert!(summary.head_time.is_some());
+        assert!(summary.is_delegate(&test::DID.parse().unwrap()));
+
+        summaries.refresh(&ctx, RID_PRIVATE.parse().unwrap());
+        assert!(summaries.get(&RID_PRIVATE.parse().unwrap()).is_none());
+    }
+}
@@ -1,29 +1,45 @@
+mod activity;
 mod delegates;
+mod events;
 mod info;
 mod node;
 mod repos;
+mod sessions;
 mod stats;
+mod webhooks;
 
 use axum::extract::State;
 use axum::response::{IntoResponse, Json};
 use axum::routing::get;
-use axum::Router;
+use axum::{middleware, Router};
 use serde_json::json;
 
-use crate::api::{Context, API_VERSION, RADICLE_VERSION};
+use crate::api::{pool, Context, API_VERSION, RADICLE_VERSION};
 
 pub fn router(ctx: Context) -> Router {
     let root_router = Router::new()
         .route("/", get(root_handler))
         .with_state(ctx.clone());
 
+    let sessions = if ctx.write_enabled() {
+        sessions::router(ctx.clone()).merge(webhooks::router(ctx.clone()))
+    } else {
+        Router::new()
+    };
+
+    let limits = ctx.pool().clone();
     let routes = Router::new()
         .merge(root_router)
+        .merge(sessions)
+        .merge(activity::router(ctx.clone()))
+        .merge(events::router(ctx.clone()))
         .merge(info::router(ctx.clone()))
         .merge(node::router(ctx.clone()))
         .merge(delegates::router(ctx.clone()))
         .merge(repos::router(ctx.clone()))
-        .merge(stats::router(ctx));
+        .merge(stats::router(ctx))
+        // Routes opt into the heavy lane; everything else is light.
+        .layer(middleware::from_fn_with_state(limits, pool::light));
 
     Router::new().nest("/v1", routes)
 }
@@ -67,6 +83,16 @@ async fn root_handler(State(ctx): State<Context>) -> impl IntoResponse {
                 "rel": "repos",
                 "type": "GET"
             },
+            {
+                "href": "/activity",
+                "rel": "activity",
+                "type": "GET"
+            },
+            {
+                "href": "/events",
+                "rel": "events",
+                "type": "GET"
+            },
             {
                 "href": "/stats",
                 "rel": "stats",
@@ -0,0 +1,265 @@
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{middleware, Router};
+use radicle_surf::Repository;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use radicle::cob::{issue, patch};
+use radicle::cob::{issue::cache::Issues as _, patch::cache::Patches as _};
+use radicle::git::Oid;
+use radicle::identity::{Did, RepoId};
+use radicle::node::AliasStore;
+use radicle::storage::{ReadRepository, ReadStorage};
+
+use crate::api::error::Error;
+use crate::api::json::Author;
+use crate::api::pool;
+use crate::api::query::MAX_PER_PAGE;
+use crate::api::Context;
+use crate::axum_extra::{cached_response, Query};
+
+pub fn router(ctx: Context) -> Router {
+    Router::new()
+        .route("/activity", get(activity_handler))
+        .route_layer(middleware::from_fn_with_state(
+            ctx.pool().clone(),
+            pool::heavy,
+        ))
+        .with_state(ctx)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ActivityQuery {
+    page: Option<usize>,
+    per_page: Option<usize>,
+}
+
+/// The repo an event happened in.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct RepoRef {
+    rid: RepoId,
+    name: String,
+}
+
+#[derive(Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum Kind {
+    /// A commit on the canonical default branch.
+    Commit {
+        id: radicle_surf::Oid,
+        summary: String,
+        author: Value,
+    },
+    IssueOpened {
+        id: issue::IssueId,
+        title: String,
+        author: Value,
+    },
+    PatchOpened {
+        id: patch::PatchId,
+        title: String,
+        author: Value,
+    },
+    PatchMerged {
+        id: patch::PatchId,
+        title: String,
+        revision: patch::RevisionId,
+        commit: Oid,
+        author: Value,
+    },
+    /// A canonical tag.
+    Tag { name: String, commit: Oid },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Event {
+    timestamp: i64,
+    repo: RepoRef,
+    #[serde(flatten)]
+    kind: Kind,
+}
+
+/// Collect the events of a single repo. Only the `limit` most recent commits
+/// are walked, since no more than that can make it onto the requested page.
+#[allow(clippy::result_large_err)]
+fn repo_events(
+    ctx: &Context,
+    rid: RepoId,
+    limit: usize,
+    aliases: &impl AliasStore,
+) -> Result<Vec<Event>, Error> {
+    let (repo, doc) = ctx.repo(rid)?;
+    let repo_ref = RepoRef {
+        rid,
+        name: doc.doc.project()?.name().to_owned(),
+    };
+    let event = |timestamp: i64, kind: Kind| Event {
+        timestamp,
+        repo: repo_ref.clone(),
+        kind,
+    };
+    let mut events = Vec::new();
+
+    let (_, head) = repo.head()?;
+    let surf = Repository::open(repo.path())?;
+    for commit in surf.history(&head.to_string())?.take(limit) {
+        let commit = commit?;
+        events.push(event(
+            commit.committer.time.seconds(),
+            Kind::Commit {
+                id: commit.id,
+                summary: commit.summary.clone(),
+                author: json!({
+                    "name": commit.author.name,
+                    "email": commit.author.email,
+                }),
+            },
+        ));
+    }
+
+    for (refname, tag) in crate::api::canonical_refs(&repo, &doc.doc)?.tags {
+        let (Some(timestamp), Some(name)) = (
+            tag.timestamp(&repo),
+            refname.as_str().strip_prefix("refs/tags/"),
+        ) else {
+            continue;
+        };
+        events.push(event(
+            timestamp,
+            Kind::Tag {
+                name: name.to_owned(),
+                commit: tag.commit,
+            },
+        ));
+    }
+
+    for result in ctx.profile.issues(&repo)?.list()? {
+        let Ok((id, issue)) = result else { continue };
+        events.push(event(
+            issue.timestamp().as_secs() as i64,
+            Kind::IssueOpened {
+                id,
+                title: issue.title().to_owned(),
+                author: Author::new(issue.author().id()).as_json(aliases),
+            },
+        ));
+    }
+
+    for result in ctx.profile.patches(&repo)?.list()? {
+        let Ok((id, patch)) = result else { continue };
+        events.push(event(
+            patch.timestamp().as_secs() as i64,
+            Kind::PatchOpened {
+                id,
+                title: patch.title().to_owned(),
+                author: Author::new(patch.author().id()).as_json(aliases),
+            },
+        ));
+        for (nid, merge) in patch.merges() {
+            events.push(event(
+                merge.timestamp.as_secs() as i64,
+                Kind::PatchMerged {
+                    id,
+                    title: patch.title().to_owned(),
+                    revision: merge.revision,
+                    commit: merge.commit,
+                    author: Author::new(&Did::from(nid)).as_json(aliases),
+                },
+            ));
+        }
+    }
+
+    Ok(events)
+}
+
+/// Recent events across every public repo seeded by this node, newest first.
+/// `GET /activity?page=<n>&perPage=<n>`
+async fn activity_handler(
+    State(ctx): State<Context>,
+    Query(qs): Query<ActivityQuery>,
+) -> impl IntoResponse {
+    let page = qs.page.unwrap_or(0);
+    let per_page = qs.per_page.unwrap_or(30).min(MAX_PER_PAGE);
+    let events = crate::api::blocking(move || {
+        let policies = ctx.profile.policies()?;
+        let aliases = ctx.profile.aliases();
+        let limit = page.saturating_add(1).saturating_mul(per_page);
+        let mut events = Vec::new();
+
+        for info in ctx.profile.storage.repositories()? {
+            if !info.doc.visibility().is_public()
+                || !policies.is_seeding(&info.rid).unwrap_or_default()
+            {
+                continue;
+            }
+            match repo_events(&ctx, info.rid, limit, &aliases) {
+                Ok(repo) => events.extend(repo),
+                Err(e) => tracing::warn!("Skipping activity of {}: {e}", info.rid),
+            }
+        }
+        // Sorting is stable, so events with equal timestamps keep their
+        // per-repo order.
+        events.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+
+        Ok::<_, Error>(
+            events
+                .into_iter()
+                .skip(page.saturating_mul(per_page))
+                .take(per_page)
+                .collect::<Vec<_>>(),
+        )
+    })
+    .await?;
+
+    Ok::<_, Error>(cached_response(events, 60))
+}
+
+#[cfg(test)]
+mod routes {
+    use axum::http::StatusCode;
+
+    use crate::test::{self, get, HEAD, ISSUE_ID, PATCH_ID, RID, RID_PRIVATE};
+
+    #[tokio::test]
+    async fn test_activity() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = get(&app, "/activity?perPage=100").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let events = response.json().await;
+        let events = events.as_array().unwrap();
+        let timestamps = events
+            .iter()
+            .map(|e| e["timestamp"].as_i64().unwrap())
+            .collect::<Vec<_>>();
+        assert!(timestamps.windows(2).all(|w| w[0] >= w[1]));
+        assert!(events
+            .iter()
+            .all(|e| e["repo"]["rid"] != RID_PRIVATE && e["repo"]["name"].is_string()));
+
+        let find = |kind: &str, id: &str| {
+            events
+                .iter()
+                .find(|e| e["type"] == kind && e["id"] == id)
+                .cloned()
+        };
+        let commit = find("commit", HEAD).unwrap();
+        assert_eq!(commit["repo"]["rid"], RID);
+        assert_eq!(commit["repo"]["name"], "hello-world");
+        assert!(find("issueOpened", ISSUE_ID).is_some());
+        assert!(find("patchOpened", PATCH_ID).is_some());
+
+        let response = get(&app, "/activity?perPage=2").await;
+        assert_eq!(response.json().await.as_array().unwrap().len(), 2);
+    }
+}
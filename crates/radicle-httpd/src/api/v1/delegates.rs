@@ -36,14 +36,21 @@ async fn delegates_repos_handler(
 
     let infos = crate::api::blocking(move || {
         let storage = &ctx.profile.storage;
-        let mut repos = match show {
-            RepoQuery::All => storage
+        let summaries = ctx.summaries();
+        let mut repos = match (show, summaries.all()) {
+            (RepoQuery::All, Some(all)) => all
+                .iter()
+                .filter(|summary| summary.is_delegate(&did))
+                .map(|summary| summary.info.rid)
+                .collect::<Vec<_>>(),
+            (RepoQuery::All, None) => storage
                 .repositories()?
                 .into_iter()
                 .filter(|repo| repo.doc.visibility().is_public())
                 .filter(|repo| repo.doc.delegates().iter().any(|d| *d == did))
+                .map(|repo| repo.rid)
                 .collect::<Vec<_>>(),
-            RepoQuery::Pinned => storage
+            (RepoQuery::Pinned, _) => storage
                 .repositories_by_id(pinned.iter())
                 .filter_map(|result| match result {
                     Ok(repo) => Some(repo),
@@ -54,22 +61,14 @@ async fn delegates_repos_handler(
                 })
                 .filter(|repo| repo.doc.visibility().is_public())
                 .filter(|repo| repo.doc.delegates().iter().any(|d| *d == did))
+                .map(|repo| repo.rid)
                 .collect::<Vec<_>>(),
         };
-        repos.sort_by_key(|p| p.rid);
+        repos.sort();
 
         let infos = repos
             .into_iter()
-            .filter_map(|id| {
-                let Ok((repo, doc)) = ctx.repo(id.rid) else {
-                    return None;
-                };
-                let Ok(repo_info) = ctx.repo_info(&repo, doc) else {
-                    return None;
-                };
-
-                Some(repo_info)
-            })
+            .filter_map(|rid| summaries.info(&ctx, rid))
             .skip(page * per_page)
             .take(per_page)
             .collect::<Vec<_>>();
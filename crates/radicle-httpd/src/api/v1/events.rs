@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use futures_util::{future, stream, StreamExt as _};
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api;
+use crate::api::error::Error;
+use crate::api::events::Envelope;
+use crate::api::Context;
+use crate::axum_extra::Query;
+
+pub fn router(ctx: Context) -> Router {
+    Router::new()
+        .route("/events", get(events_handler))
+        .with_state(ctx)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct EventsQuery {
+    /// Only stream events about this repo.
+    rid: Option<String>,
+}
+
+/// Stream node events about public repos as Server-Sent Events.
+/// `GET /events?rid=<rid>`
+///
+/// Clients reconnecting with a `Last-Event-ID` header first receive the
+/// events they missed, as far as the backlog goes. A client that falls too
+/// far behind is disconnected, so that it reconnects and catches up from the
+/// backlog.
+async fn events_handler(
+    State(ctx): State<Context>,
+    Query(qs): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let rid = match qs.rid {
+        Some(rid) => {
+            let rid = ctx.resolve_repo(&rid)?;
+            let ctx = ctx.clone();
+            // Checks that the repo exists and is public.
+            api::blocking(move || ctx.repo(rid).map(|_| ())).await?;
+
+            Some(rid)
+        }
+        None => None,
+    };
+    let last_id = headers
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok());
+    let (replay, rx) = ctx.events().subscribe(last_id);
+
+    let live = stream::unfold(rx, |mut rx| async move {
+        match rx.recv().await {
+            Ok(envelope) => Some((envelope, rx)),
+            Err(RecvError::Lagged(n)) => {
+                tracing::debug!("Event stream client lagged by {n} events, disconnecting");
+                None
+            }
+            Err(RecvError::Closed) => None,
+        }
+    });
+    let events = stream::iter(replay)
+        .chain(live)
+        .filter(move |envelope| future::ready(rid.is_none_or(|rid| envelope.rid == rid)))
+        .map(|envelope: Arc<Envelope>| {
+            Event::default()
+                .id(envelope.id.to_string())
+                .event(&envelope.kind)
+                .json_data(&envelope.data)
+        });
+
+    Ok::<_, Error>(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod routes {
+    use std::str::FromStr;
+
+    use axum::body::Body;
+    use axum::http::{header, Request, StatusCode};
+    use futures_util::StreamExt as _;
+    use radicle::identity::RepoId;
+    use serde_json::json;
+    use tower::ServiceExt as _;
+
+    use crate::test::{self, get, RID, RID_PRIVATE};
+
+    #[tokio::test]
+    async fn test_events_replay() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let rid = RepoId::from_str(RID).unwrap();
+        let other = RepoId::from_str(RID_PRIVATE).unwrap();
+
+        let first = ctx.events().publish(rid, json!({ "type": "refsFetched" }));
+        ctx.events()
+            .publish(other, json!({ "type": "seedDiscovered" }));
+        let last = ctx
+            .events()
+            .publish(rid, json!({ "type": "canonicalRefUpdated" }));
+
+        let request = Request::builder()
+            .uri(format!("/events?rid={RID}"))
+            .header("Last-Event-ID", (first.id - 1).to_string())
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+
+        let mut body = response.into_body().into_data_stream();
+        let mut text = String::new();
+        while !text.contains(&format!("id: {}\n", last.id)) {
+            let chunk = body.next().await.unwrap().unwrap();
+            text.push_str(std::str::from_utf8(&chunk).unwrap());
+        }
+        assert!(text.contains(&format!("id: {}\n", first.id)));
+        assert!(text.contains("event: refsFetched\n"));
+        assert!(text.contains("data: {\"type\":\"refsFetched\"}\n"));
+        assert!(text.contains("event: canonicalRefUpdated\n"));
+        assert!(!text.contains("seedDiscovered"));
+    }
+
+    #[tokio::test]
+    async fn test_events_private_repo() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = get(&app, format!("/events?rid={RID_PRIVATE}")).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
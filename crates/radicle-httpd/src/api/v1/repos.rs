@@ -1,12 +1,16 @@
+mod actions;
+mod feeds;
+mod identity;
 mod job;
+mod merge;
 
 use std::collections::{BTreeMap, HashMap};
 
 use axum::extract::{DefaultBodyLimit, State};
-use axum::http::header;
+use axum::http::{header, HeaderMap};
 use axum::response::IntoResponse;
 use axum::routing::get;
-use axum::{Json, Router};
+use axum::{middleware, Json, Router};
 use hyper::StatusCode;
 use radicle_surf::blob::BlobRef;
 use radicle_surf::{diff, Glob, Oid, Repository};
@@ -15,24 +19,34 @@ use serde_json::json;
 
 use radicle::cob::{issue::cache::Issues as _, patch::cache::Patches as _};
 use radicle::git::fmt::{Qualified, RefString};
+use radicle::node::routing::Store as _;
 use radicle::node::{Alias, AliasStore, NodeId};
 use radicle::storage::{ReadRepository, RemoteRepository};
 
 use crate::api;
 use crate::api::error::Error;
+use crate::api::pool;
 use crate::api::query::{CobsQuery, PaginationQuery, RepoQuery, MAX_PER_PAGE, MAX_QUERY_LEN};
 use crate::api::search::SearchQueryString;
 use crate::api::Context;
 use crate::api::PeelToCommit;
 use crate::axum_extra::{cached_response, immutable_response, Path, Query};
+use crate::cache::{self, Endpoint, Lookup};
+
+use job::FindJobs as _;
 
 const MAX_BODY_LIMIT: usize = 4_194_304;
 
 pub fn router(ctx: Context) -> Router {
-    Router::new()
-        .route("/repos", get(repo_root_handler))
-        .route("/repos/search", get(repo_search_handler))
-        .route("/repos/{rid}", get(repo_handler))
+    let actions = if ctx.write_enabled() {
+        actions::router(ctx.clone())
+    } else {
+        Router::new()
+    };
+
+    // Endpoints that walk trees, diffs or history get their own lane, so
+    // they can't starve listings and issue or patch reads.
+    let heavy = Router::new()
         .route("/repos/{rid}/commits", get(history_handler))
         .route("/repos/{rid}/commits/{sha}", get(commit_handler))
         .route("/repos/{rid}/diff/{base}/{oid}", get(diff_handler))
@@ -48,16 +62,45 @@ pub fn router(ctx: Context) -> Router {
             "/repos/{rid}/stats/commits/{sha}",
             get(stats_commits_handler),
         )
-        .route("/repos/{rid}/remotes", get(remotes_handler))
-        .route("/repos/{rid}/remotes/{peer}", get(remote_handler))
         .route("/repos/{rid}/blob/{sha}/{*path}", get(blob_handler))
         .route("/repos/{rid}/readme/{sha}", get(readme_handler))
+        .route(
+            "/repos/{rid}/feeds/commits.atom",
+            get(feeds::commits_handler),
+        )
+        .route(
+            "/repos/{rid}/patches/{id}/mergeability",
+            get(merge::handler),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            ctx.pool().clone(),
+            pool::heavy,
+        ));
+
+    Router::new()
+        .route("/repos", get(repo_root_handler))
+        .route("/repos/search", get(repo_search_handler))
+        .route("/repos/{rid}", get(repo_handler))
+        .route(
+            "/repos/{rid}/identity/revisions",
+            get(identity::revisions_handler),
+        )
+        .route("/repos/{rid}/remotes", get(remotes_handler))
+        .route("/repos/{rid}/remotes/{peer}", get(remote_handler))
+        .route("/repos/{rid}/jobs", get(job::list_handler))
         .route("/repos/{rid}/jobs/{sha}", get(job::handler))
         .route("/repos/{rid}/issues", get(issues_handler))
         .route("/repos/{rid}/issues/{id}", get(issue_handler))
         .route("/repos/{rid}/patches", get(patches_handler))
         .route("/repos/{rid}/patches/{id}", get(patch_handler))
+        .route("/repos/{rid}/feeds/issues.atom", get(feeds::issues_handler))
+        .route(
+            "/repos/{rid}/feeds/patches.atom",
+            get(feeds::patches_handler),
+        )
+        .merge(heavy)
         .with_state(ctx)
+        .merge(actions)
         .layer(DefaultBodyLimit::max(MAX_BODY_LIMIT))
 }
 
@@ -79,9 +122,9 @@ mod storage {
     use crate::api::Context;
     use crate::axum_extra::cached_response;
 
-    /// Repo listing via storage walk. Activity/seeding sorts are collapsed
-    /// to rid sort — walking storage for every repo per request is too
-    /// expensive without a pre-computed index.
+    /// Repo listing from the repo summaries, or via storage walk until
+    /// they're computed. Activity/seeding sorts are then collapsed to rid
+    /// sort — walking storage for every repo per request is too expensive.
     #[allow(clippy::result_large_err)]
     pub fn list_repos(
         ctx: &Context,
@@ -91,8 +134,10 @@ mod storage {
         per_page: usize,
         web_config: &radicle::web::Config,
     ) -> Result<Vec<api::repo::Info>, Error> {
+        let summaries = ctx.summaries();
         let sort = if matches!(show, RepoQuery::All)
             && matches!(sort, RepoSort::Activity | RepoSort::Seeding)
+            && !summaries.is_ready()
         {
             RepoSort::Rid
         } else {
@@ -103,13 +148,15 @@ mod storage {
         let pinned = &web_config.pinned;
         let policies = ctx.profile.policies()?;
 
-        let repos = match show {
-            RepoQuery::All => storage
+        let mut repos = match (show, summaries.all()) {
+            (RepoQuery::All, Some(all)) => all.iter().map(|s| s.info.rid).collect::<Vec<_>>(),
+            (RepoQuery::All, None) => storage
                 .repositories()?
                 .into_iter()
                 .filter(|repo| repo.doc.visibility().is_public())
+                .map(|repo| repo.rid)
                 .collect::<Vec<_>>(),
-            RepoQuery::Pinned => storage
+            (RepoQuery::Pinned, _) => storage
                 .repositories_by_id(pinned.repositories.iter())
                 .filter_map(|result| match result {
                     Ok(repo) => Some(repo),
@@ -119,22 +166,17 @@ mod storage {
                     }
                 })
                 .filter(|repo| repo.doc.visibility().is_public())
+                .map(|repo| repo.rid)
                 .collect::<Vec<_>>(),
         };
+        repos.retain(|rid| policies.is_seeding(rid).unwrap_or_default());
 
         let infos = match sort {
             RepoSort::Rid => {
-                let mut repos = repos;
-                repos.sort_by_key(|p| p.rid);
+                repos.sort();
                 repos
                     .into_iter()
-                    .filter_map(|info| {
-                        if !policies.is_seeding(&info.rid).unwrap_or_default() {
-                            return None;
-                        }
-                        let (repo, doc) = ctx.repo(info.rid).ok()?;
-                        ctx.repo_info(&repo, doc).ok()
-                    })
+                    .filter_map(|rid| summaries.info(ctx, rid))
                     .skip(page * per_page)
                     .take(per_page)
                     .collect::<Vec<_>>()
@@ -142,15 +184,15 @@ mod storage {
             RepoSort::Activity => {
                 let mut with_time: Vec<(radicle::identity::RepoId, i64)> = repos
                     .into_iter()
-                    .filter_map(|info| {
-                        if !policies.is_seeding(&info.rid).unwrap_or_default() {
-                            return None;
+                    .filter_map(|rid| {
+                        if let Some(summary) = summaries.get(&rid) {
+                            return Some((rid, summary.head_time?));
                         }
-                        let (repo, _doc) = ctx.repo(info.rid).ok()?;
+                        let (repo, _doc) = ctx.repo(rid).ok()?;
                         let surf = Repository::open(repo.path()).ok()?;
                         let head = surf.head().ok()?;
                         let commit = surf.commit(head).ok()?;
-                        Some((info.rid, commit.committer.time.seconds()))
+                        Some((rid, commit.committer.time.seconds()))
                     })
                     .collect();
                 with_time.sort_by_key(|x| std::cmp::Reverse(x.1));
@@ -158,22 +200,19 @@ mod storage {
                     .into_iter()
                     .skip(page * per_page)
                     .take(per_page)
-                    .filter_map(|(rid, _)| {
-                        let (repo, doc) = ctx.repo(rid).ok()?;
-                        ctx.repo_info(&repo, doc).ok()
-                    })
+                    .filter_map(|(rid, _)| summaries.info(ctx, rid))
                     .collect::<Vec<_>>()
             }
             RepoSort::Seeding => {
                 let db = ctx.profile.database()?;
                 let mut with_count: Vec<(radicle::identity::RepoId, usize)> = repos
                     .into_iter()
-                    .filter_map(|info| {
-                        if !policies.is_seeding(&info.rid).unwrap_or_default() {
-                            return None;
-                        }
-                        let count = db.count(&info.rid).unwrap_or_default();
-                        Some((info.rid, count))
+                    .map(|rid| {
+                        let count = match summaries.get(&rid) {
+                            Some(summary) => summary.info.seeding,
+                            None => db.count(&rid).unwrap_or_default(),
+                        };
+                        (rid, count)
                     })
                     .collect();
                 with_count.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
@@ -181,10 +220,7 @@ mod storage {
                     .into_iter()
                     .skip(page * per_page)
                     .take(per_page)
-                    .filter_map(|(rid, _)| {
-                        let (repo, doc) = ctx.repo(rid).ok()?;
-                        ctx.repo_info(&repo, doc).ok()
-                    })
+                    .filter_map(|(rid, _)| summaries.info(ctx, rid))
                     .collect::<Vec<_>>()
             }
         };
@@ -210,11 +246,19 @@ mod storage {
             let storage = &ctx.profile.storage;
             let aliases = &ctx.profile.aliases();
             let db = &ctx.profile.database()?;
-            let found_repos = storage
-                .repositories()?
-                .into_iter()
-                .filter_map(|info| SearchResult::new(&q, info, db, aliases))
-                .collect::<BTreeSet<SearchResult>>();
+            let found_repos = match ctx.summaries().all() {
+                Some(all) => all
+                    .iter()
+                    .filter_map(|s| {
+                        SearchResult::matching(&q, s.info.rid, &s.doc, || s.info.seeding, aliases)
+                    })
+                    .collect::<BTreeSet<SearchResult>>(),
+                None => storage
+                    .repositories()?
+                    .into_iter()
+                    .filter_map(|info| SearchResult::new(&q, info, db, aliases))
+                    .collect::<BTreeSet<SearchResult>>(),
+            };
 
             Ok::<_, Error>(
                 found_repos
@@ -355,8 +399,7 @@ mod listing {
                 if !policies.is_seeding(&rid).unwrap_or_default() {
                     return None;
                 }
-                let (repo, doc) = ctx.repo(rid).ok()?;
-                ctx.repo_info(&repo, doc).ok()
+                ctx.summaries().info(ctx, rid)
             })
             .collect())
     }
@@ -404,15 +447,39 @@ async fn repo_search_handler(
 
 /// Get repo metadata.
 /// `GET /repos/:rid`
-async fn repo_handler(State(ctx): State<Context>, Path(rid): Path<String>) -> impl IntoResponse {
+async fn repo_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let info = api::blocking(move || {
-        let (repo, doc) = ctx.repo(rid)?;
-        ctx.repo_info(&repo, doc)
+    let ticket = match ctx
+        .cache_get_tagged(cache::Key::new(Endpoint::Repo, rid, ""), &headers)
+        .await?
+    {
+        Lookup::Hit(response) => return Ok(response),
+        Lookup::Miss(ticket) => ticket,
+    };
+    let (etag, info) = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, doc) = ctx.repo(rid)?;
+            // The seeding count and alias are part of the repo info, but not of
+            // its state in storage.
+            let seeding = ctx.profile.database()?.count(&rid).unwrap_or_default();
+            let alias = ctx.repo_alias(&rid).unwrap_or_default();
+            let etag = api::repo_etag(&repo, format!("{seeding} {alias}").as_bytes())?;
+            if etag.matches(&headers) {
+                return Ok((etag, None));
+            }
+            let info = ctx.repo_info(&repo, doc)?;
+
+            Ok::<_, Error>((etag, Some(info)))
+        }
     })
     .await?;
 
-    Ok::<_, Error>(Json(info))
+    ctx.cache_put_tagged(ticket, etag, info).await
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -426,11 +493,12 @@ pub struct CommitsQueryString {
 }
 
 /// Get repo commit range.
-/// `GET /repos/:rid/commits?parent=<sha>`
+/// `GET /repos/:rid/commits?parent=<sha>&ci=<bool>`
 async fn history_handler(
     State(ctx): State<Context>,
     Path(rid): Path<String>,
     Query(qs): Query<CommitsQueryString>,
+    Query(job::CiQuery { ci }): Query<job::CiQuery>,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
     let CommitsQueryString {
@@ -443,8 +511,9 @@ async fn history_handler(
 
     // If the parent commit is provided, the response depends only on the query
     // string and not on the state of the repository. This means we can instruct
-    // the caches to treat the response as immutable.
-    let is_immutable = parent.is_some();
+    // the caches to treat the response as immutable. CI summaries change as
+    // jobs run, so they opt out of this.
+    let is_immutable = parent.is_some() && !ci;
 
     let commits = api::blocking(move || {
         let (repo, _) = ctx.repo(rid)?;
@@ -453,6 +522,11 @@ async fn history_handler(
             Some(commit) => commit,
             None => head.to_string(),
         };
+        let summaries = if ci {
+            Some(job::JobsSource::new(&ctx, rid).summaries()?)
+        } else {
+            None
+        };
         let repo = Repository::open(repo.path())?;
 
         // If a pagination is defined, we do not want to paginate the commits, and we return all of them on the first page.
@@ -468,7 +542,11 @@ async fn history_handler(
             .filter_map(|commit| {
                 let commit = commit.ok()?;
                 let time = commit.committer.time.seconds();
-                let commit = api::json::commit::Commit::new(&commit).as_json();
+                let id = radicle::git::Oid::from(radicle::git::raw::Oid::from(commit.id));
+                let mut commit = api::json::commit::Commit::new(&commit).as_json();
+                if let Some(summaries) = &summaries {
+                    job::annotate(&mut commit, id, summaries);
+                }
                 match (since, until) {
                     (Some(since), Some(until)) if time >= since && time < until => Some(commit),
                     (Some(since), None) if time >= since => Some(commit),
@@ -496,69 +574,88 @@ async fn history_handler(
 async fn commit_handler(
     State(ctx): State<Context>,
     Path((rid, sha)): Path<(String, Oid)>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let response = api::blocking(move || {
-        let (repo, _) = ctx.repo(rid)?;
-        let repo = Repository::open(repo.path())?;
-        let commit = repo.commit(sha)?;
-
-        let diff = repo.diff_commit(commit.id)?;
-        let glob = Glob::all_heads().branches().and(Glob::all_remotes());
-        let branches: Vec<String> = repo
-            .revision_branches(commit.id, glob)?
-            .iter()
-            .map(|b| b.refname().to_string())
-            .collect();
-
-        let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
-        diff.files().for_each(|file_diff| match file_diff {
-            diff::FileDiff::Added(added) => {
-                if let Ok(blob) = repo.blob_ref(added.new.oid) {
-                    files.insert(blob.id(), blob);
-                }
+    let ticket = match ctx
+        .cache_get_tagged(cache::Key::new(Endpoint::Commit, rid, sha), &headers)
+        .await?
+    {
+        Lookup::Hit(response) => return Ok(response),
+        Lookup::Miss(ticket) => ticket,
+    };
+    let (etag, response) = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, _) = ctx.repo(rid)?;
+            // The commit never changes, but the branches containing it do.
+            let etag = api::repo_etag(&repo, sha.to_string().as_bytes())?;
+            if etag.matches(&headers) {
+                return Ok((etag, None));
             }
-            diff::FileDiff::Deleted(deleted) => {
-                if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
-                    files.insert(old_blob.id(), old_blob);
+            let repo = Repository::open(repo.path())?;
+            let commit = repo.commit(sha)?;
+
+            let diff = repo.diff_commit(commit.id)?;
+            let glob = Glob::all_heads().branches().and(Glob::all_remotes());
+            let branches: Vec<String> = repo
+                .revision_branches(commit.id, glob)?
+                .iter()
+                .map(|b| b.refname().to_string())
+                .collect();
+
+            let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
+            diff.files().for_each(|file_diff| match file_diff {
+                diff::FileDiff::Added(added) => {
+                    if let Ok(blob) = repo.blob_ref(added.new.oid) {
+                        files.insert(blob.id(), blob);
+                    }
                 }
-            }
-            diff::FileDiff::Modified(modified) => {
-                if let (Ok(old_blob), Ok(new_blob)) = (
-                    repo.blob_ref(modified.old.oid),
-                    repo.blob_ref(modified.new.oid),
-                ) {
-                    files.insert(old_blob.id(), old_blob);
-                    files.insert(new_blob.id(), new_blob);
+                diff::FileDiff::Deleted(deleted) => {
+                    if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
+                        files.insert(old_blob.id(), old_blob);
+                    }
                 }
-            }
-            diff::FileDiff::Moved(moved) => {
-                if let (Ok(old_blob), Ok(new_blob)) =
-                    (repo.blob_ref(moved.old.oid), repo.blob_ref(moved.new.oid))
-                {
-                    files.insert(old_blob.id(), old_blob);
-                    files.insert(new_blob.id(), new_blob);
+                diff::FileDiff::Modified(modified) => {
+                    if let (Ok(old_blob), Ok(new_blob)) = (
+                        repo.blob_ref(modified.old.oid),
+                        repo.blob_ref(modified.new.oid),
+                    ) {
+                        files.insert(old_blob.id(), old_blob);
+                        files.insert(new_blob.id(), new_blob);
+                    }
                 }
-            }
-            diff::FileDiff::Copied(copied) => {
-                if let (Ok(old_blob), Ok(new_blob)) =
-                    (repo.blob_ref(copied.old.oid), repo.blob_ref(copied.new.oid))
-                {
-                    files.insert(old_blob.id(), old_blob);
-                    files.insert(new_blob.id(), new_blob);
+                diff::FileDiff::Moved(moved) => {
+                    if let (Ok(old_blob), Ok(new_blob)) =
+                        (repo.blob_ref(moved.old.oid), repo.blob_ref(moved.new.oid))
+                    {
+                        files.insert(old_blob.id(), old_blob);
+                        files.insert(new_blob.id(), new_blob);
+                    }
                 }
-            }
-        });
-
-        Ok::<_, Error>(json!({
-          "commit": api::json::commit::Commit::new(&commit).as_json(),
-          "diff": api::json::diff::Diff::new(&diff).as_json(),
-          "files": files,
-          "branches": branches
-        }))
+                diff::FileDiff::Copied(copied) => {
+                    if let (Ok(old_blob), Ok(new_blob)) =
+                        (repo.blob_ref(copied.old.oid), repo.blob_ref(copied.new.oid))
+                    {
+                        files.insert(old_blob.id(), old_blob);
+                        files.insert(new_blob.id(), new_blob);
+                    }
+                }
+            });
+
+            let response = json!({
+              "commit": api::json::commit::Commit::new(&commit).as_json(),
+              "diff": api::json::diff::Diff::new(&diff).as_json(),
+              "files": files,
+              "branches": branches
+            });
+
+            Ok::<_, Error>((etag, Some(response)))
+        }
     })
     .await?;
-    Ok::<_, Error>(immutable_response(response))
+
+    ctx.cache_put_tagged(ticket, etag, response).await
 }
 
 /// Get diff between two commits
@@ -568,68 +665,75 @@ async fn diff_handler(
     Path((rid, base, oid)): Path<(String, Oid, Oid)>,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let response = api::blocking(move || {
-        let (repo, _) = ctx.repo(rid)?;
-        let repo = Repository::open(repo.path())?;
-        let base = repo.commit(base)?;
-        let commit = repo.commit(oid)?;
-        let diff = repo.diff(base.id, commit.id)?;
-        let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
-        diff.files().for_each(|file_diff| match file_diff {
-            diff::FileDiff::Added(added) => {
-                if let Ok(new_blob) = repo.blob_ref(added.new.oid) {
-                    files.insert(new_blob.id(), new_blob);
-                }
-            }
-            diff::FileDiff::Deleted(deleted) => {
-                if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
-                    files.insert(old_blob.id(), old_blob);
+    let key = cache::Key::new(Endpoint::Diff, rid, format!("{base}..{oid}"));
+    if let Some(hit) = ctx.cache_get(&key).await? {
+        return Ok(hit);
+    }
+    let response = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, _) = ctx.repo(rid)?;
+            let repo = Repository::open(repo.path())?;
+            let base = repo.commit(base)?;
+            let commit = repo.commit(oid)?;
+            let diff = repo.diff(base.id, commit.id)?;
+            let mut files: HashMap<Oid, BlobRef<'_>> = HashMap::new();
+            diff.files().for_each(|file_diff| match file_diff {
+                diff::FileDiff::Added(added) => {
+                    if let Ok(new_blob) = repo.blob_ref(added.new.oid) {
+                        files.insert(new_blob.id(), new_blob);
+                    }
                 }
-            }
-            diff::FileDiff::Modified(modified) => {
-                if let (Ok(new_blob), Ok(old_blob)) = (
-                    repo.blob_ref(modified.old.oid),
-                    repo.blob_ref(modified.new.oid),
-                ) {
-                    files.insert(new_blob.id(), new_blob);
-                    files.insert(old_blob.id(), old_blob);
+                diff::FileDiff::Deleted(deleted) => {
+                    if let Ok(old_blob) = repo.blob_ref(deleted.old.oid) {
+                        files.insert(old_blob.id(), old_blob);
+                    }
                 }
-            }
-            diff::FileDiff::Moved(moved) => {
-                if let (Ok(new_blob), Ok(old_blob)) =
-                    (repo.blob_ref(moved.new.oid), repo.blob_ref(moved.old.oid))
-                {
-                    files.insert(new_blob.id(), new_blob);
-                    files.insert(old_blob.id(), old_blob);
+                diff::FileDiff::Modified(modified) => {
+                    if let (Ok(new_blob), Ok(old_blob)) = (
+                        repo.blob_ref(modified.old.oid),
+                        repo.blob_ref(modified.new.oid),
+                    ) {
+                        files.insert(new_blob.id(), new_blob);
+                        files.insert(old_blob.id(), old_blob);
+                    }
                 }
-            }
-            diff::FileDiff::Copied(copied) => {
-                if let (Ok(new_blob), Ok(old_blob)) =
-                    (repo.blob_ref(copied.new.oid), repo.blob_ref(copied.old.oid))
-                {
-                    files.insert(new_blob.id(), new_blob);
-                    files.insert(old_blob.id(), old_blob);
+                diff::FileDiff::Moved(moved) => {
+                    if let (Ok(new_blob), Ok(old_blob)) =
+                        (repo.blob_ref(moved.new.oid), repo.blob_ref(moved.old.oid))
+                    {
+                        files.insert(new_blob.id(), new_blob);
+                        files.insert(old_blob.id(), old_blob);
+                    }
                 }
-            }
-        });
-
-        let commits = repo
-            .history(commit.id)?
-            .take_while(|c| {
-                if let Ok(c) = c {
-                    c.id != base.id
-                } else {
-                    false
+                diff::FileDiff::Copied(copied) => {
+                    if let (Ok(new_blob), Ok(old_blob)) =
+                        (repo.blob_ref(copied.new.oid), repo.blob_ref(copied.old.oid))
+                    {
+                        files.insert(new_blob.id(), new_blob);
+                        files.insert(old_blob.id(), old_blob);
+                    }
                 }
-            })
-            .map(|r| r.map(|c| api::json::commit::Commit::new(&c).as_json()))
-            .collect::<Result<Vec<_>, _>>()?;
+            });
+
+            let commits = repo
+                .history(commit.id)?
+                .take_while(|c| {
+                    if let Ok(c) = c {
+                        c.id != base.id
+                    } else {
+                        false
+                    }
+                })
+                .map(|r| r.map(|c| api::json::commit::Commit::new(&c).as_json()))
+                .collect::<Result<Vec<_>, _>>()?;
 
-        Ok::<_, Error>(json!({ "diff": diff, "files": files, "commits": commits }))
+            Ok::<_, Error>(json!({ "diff": diff, "files": files, "commits": commits }))
+        }
     })
     .await?;
 
-    Ok::<_, Error>(immutable_response(response))
+    ctx.cache_put(key, &response).await
 }
 
 /// Get diff stats between two commits.
@@ -679,20 +783,23 @@ fn diff_stats(
     }))
 }
 
-/// Tally `git diff --numstat` between two commits into `(files, insertions,
-/// deletions)`. Returns `None` if git is unavailable or its output can't be
-/// parsed, so the caller can fall back to the surf diff.
-/// Run `git` in `repo_dir` with `args` and return its stdout on success, or
-/// `None` if the binary is unavailable or it exits non-zero.
+/// A `git` command run in `repo_dir`.
 ///
 /// User and system git config are pinned to `/dev/null` so output stays
 /// deterministic and independent of the machine's configuration (e.g.
 /// `diff.renames`, `diff.algorithm`).
-fn git_output(repo_dir: &std::path::Path, args: &[&str]) -> Option<Vec<u8>> {
-    std::process::Command::new("git")
-        .current_dir(repo_dir)
+fn git_command(repo_dir: &std::path::Path) -> std::process::Command {
+    let mut cmd = std::process::Command::new("git");
+    cmd.current_dir(repo_dir)
         .env("GIT_CONFIG_GLOBAL", "/dev/null")
-        .env("GIT_CONFIG_SYSTEM", "/dev/null")
+        .env("GIT_CONFIG_SYSTEM", "/dev/null");
+    cmd
+}
+
+/// Run `git` in `repo_dir` with `args` and return its stdout on success, or
+/// `None` if the binary is unavailable or it exits non-zero.
+fn git_output(repo_dir: &std::path::Path, args: &[&str]) -> Option<Vec<u8>> {
+    git_command(repo_dir)
         .args(args)
         .output()
         .ok()
@@ -700,6 +807,9 @@ fn git_output(repo_dir: &std::path::Path, args: &[&str]) -> Option<Vec<u8>> {
         .map(|output| output.stdout)
 }
 
+/// Tally `git diff --numstat` between two commits into `(files, insertions,
+/// deletions)`. Returns `None` if git is unavailable or its output can't be
+/// parsed, so the caller can fall back to the surf diff.
 fn numstat(repo_dir: &std::path::Path, base: Oid, head: Oid) -> Option<(usize, usize, usize)> {
     let (base, head) = (base.to_string(), head.to_string());
     let stdout = git_output(
@@ -775,37 +885,22 @@ async fn tree_handler(
     Path((rid, sha, path)): Path<(String, Oid, String)>,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    if let Some(ref cache) = ctx.cache {
-        let hit = {
-            let mut cache = cache.tree.lock().await;
-            cache.get(&(rid, sha, path.clone())).cloned()
-        };
-        if let Some(response) = hit {
-            // Enforce repository visibility even on cache hits.
-            let ctx = ctx.clone();
-            api::blocking(move || ctx.repo(rid).map(|_| ())).await?;
-            return Ok::<_, Error>(immutable_response(response));
-        }
+    let key = cache::Key::new(Endpoint::Tree, rid, format!("{sha}/{path}"));
+    if let Some(hit) = ctx.cache_get(&key).await? {
+        return Ok(hit);
     }
-
-    let response = {
+    let response = api::blocking({
         let ctx = ctx.clone();
-        let path = path.clone();
-        api::blocking(move || {
+        move || {
             let (repo, _) = ctx.repo(rid)?;
             let repo = Repository::open(repo.path())?;
             let tree = repo.tree(sha, &path)?;
             Ok::<_, Error>(api::json::commit::Tree::new(&tree).as_json(&path))
-        })
-        .await?
-    };
-
-    if let Some(cache) = &ctx.cache {
-        let cache = &mut cache.tree.lock().await;
-        cache.put((rid, sha, path.clone()), response.clone());
-    }
+        }
+    })
+    .await?;
 
-    Ok::<_, Error>(immutable_response(response))
+    ctx.cache_put(key, &response).await
 }
 
 /// Get repo source tree stats.
@@ -815,14 +910,21 @@ async fn stats_tree_handler(
     Path((rid, sha)): Path<(String, Oid)>,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let stats = api::blocking(move || {
-        let (repo, _) = ctx.repo(rid)?;
-        let repo = Repository::open(repo.path())?;
-        Ok::<_, Error>(repo.stats_from(&sha)?)
+    let key = cache::Key::new(Endpoint::Stats, rid, sha);
+    if let Some(hit) = ctx.cache_get(&key).await? {
+        return Ok(hit);
+    }
+    let stats = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, _) = ctx.repo(rid)?;
+            let repo = Repository::open(repo.path())?;
+            Ok::<_, Error>(repo.stats_from(&sha)?)
+        }
     })
     .await?;
 
-    Ok::<_, Error>(immutable_response(stats))
+    ctx.cache_put(key, &stats).await
 }
 
 /// Get the number of commits reachable from a commit.
@@ -876,23 +978,41 @@ fn commit_count(repo: &radicle::storage::git::Repository, head: Oid) -> Result<u
 
 /// Get all repo remotes.
 /// `GET /repos/:rid/remotes`
-async fn remotes_handler(State(ctx): State<Context>, Path(rid): Path<String>) -> impl IntoResponse {
+async fn remotes_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let remotes = api::blocking(move || {
-        let (repo, doc) = ctx.repo(rid)?;
-        let delegates = doc.delegates();
-        let aliases = &ctx.profile.aliases();
+    let ticket = match ctx
+        .cache_get_tagged(cache::Key::new(Endpoint::Remotes, rid, ""), &headers)
+        .await?
+    {
+        Lookup::Hit(response) => return Ok(response),
+        Lookup::Miss(ticket) => ticket,
+    };
+    let (etag, remotes) = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, doc) = ctx.repo(rid)?;
+            let etag = api::repo_etag(&repo, &[])?;
+            if etag.matches(&headers) {
+                return Ok((etag, None));
+            }
+            let delegates = doc.delegates();
+            let aliases = &ctx.profile.aliases();
 
-        let remotes = repo
-            .remotes()?
-            .filter_map(|r| r.map(|r| r.1).ok())
-            .map(|remote| remote_info(&repo, &remote, delegates, aliases))
-            .collect::<Vec<_>>();
-        Ok::<_, Error>(remotes)
+            let remotes = repo
+                .remotes()?
+                .filter_map(|r| r.map(|r| r.1).ok())
+                .map(|remote| remote_info(&repo, &remote, delegates, aliases))
+                .collect::<Vec<_>>();
+            Ok::<_, Error>((etag, Some(remotes)))
+        }
     })
     .await?;
 
-    Ok::<_, Error>(Json(remotes))
+    ctx.cache_put_tagged(ticket, etag, remotes).await
 }
 
 /// Get repo remote.
@@ -900,19 +1020,24 @@ async fn remotes_handler(State(ctx): State<Context>, Path(rid): Path<String>) ->
 async fn remote_handler(
     State(ctx): State<Context>,
     Path((rid, node_id)): Path<(String, NodeId)>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let info = api::blocking(move || {
+    let response = api::blocking(move || {
         let (repo, doc) = ctx.repo(rid)?;
+        let etag = api::repo_etag(&repo, &[])?;
+        if etag.matches(&headers) {
+            return Ok(etag.not_modified());
+        }
         let delegates = doc.delegates();
         let aliases = &ctx.profile.aliases();
         let remote = repo.remote(&node_id)?;
 
-        Ok::<_, Error>(remote_info(&repo, &remote, delegates, aliases))
+        Ok::<_, Error>(etag.response(remote_info(&repo, &remote, delegates, aliases)))
     })
     .await?;
 
-    Ok::<_, Error>(Json(info))
+    Ok::<_, Error>(response)
 }
 
 /// Information tracked per remote peer in Radicle storage.
@@ -1122,23 +1247,30 @@ async fn blob_handler(
     Path((rid, sha, path)): Path<(String, Oid, String)>,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let outcome = api::blocking(move || {
-        let (repo, _) = ctx.repo(rid)?;
-        let surf_repo = Repository::open(repo.path())?;
-
-        Ok::<_, Error>(match read_blob(&repo, &surf_repo, sha, &path)? {
-            BlobData::TooLarge => BlobOutcome::TooLarge,
-            BlobData::Blob {
-                is_binary,
-                content,
-                last_commit,
-            } => BlobOutcome::Json(api::json::commit::blob_json(
-                is_binary,
-                &content,
-                &path,
-                &last_commit,
-            )),
-        })
+    let key = cache::Key::new(Endpoint::Blob, rid, format!("{sha}/{path}"));
+    if let Some(hit) = ctx.cache_get(&key).await? {
+        return Ok(hit);
+    }
+    let outcome = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, _) = ctx.repo(rid)?;
+            let surf_repo = Repository::open(repo.path())?;
+
+            Ok::<_, Error>(match read_blob(&repo, &surf_repo, sha, &path)? {
+                BlobData::TooLarge => BlobOutcome::TooLarge,
+                BlobData::Blob {
+                    is_binary,
+                    content,
+                    last_commit,
+                } => BlobOutcome::Json(api::json::commit::blob_json(
+                    is_binary,
+                    &content,
+                    &path,
+                    &last_commit,
+                )),
+            })
+        }
     })
     .await?;
 
@@ -1151,7 +1283,7 @@ async fn blob_handler(
             )
                 .into_response(),
         ),
-        BlobOutcome::Json(value) => Ok::<_, Error>(immutable_response(value).into_response()),
+        BlobOutcome::Json(value) => ctx.cache_put(key, &value).await,
     }
 }
 
@@ -1162,43 +1294,50 @@ async fn readme_handler(
     Path((rid, sha)): Path<(String, Oid)>,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let outcome = api::blocking(move || {
-        let (repo, _) = ctx.repo(rid)?;
-        let surf_repo = Repository::open(repo.path())?;
-        let paths = [
-            "README",
-            "README.md",
-            "README.markdown",
-            "README.txt",
-            "README.rst",
-            "README.org",
-            "Readme.md",
-        ];
-
-        for path in paths
-            .iter()
-            .map(ToString::to_string)
-            .chain(paths.iter().map(|p| p.to_lowercase()))
-        {
-            match read_blob(&repo, &surf_repo, sha, &path) {
-                Ok(BlobData::TooLarge) => return Ok::<_, Error>(BlobOutcome::TooLarge),
-                Ok(BlobData::Blob {
-                    is_binary,
-                    content,
-                    last_commit,
-                }) => {
-                    return Ok::<_, Error>(BlobOutcome::Json(api::json::commit::blob_json(
+    let key = cache::Key::new(Endpoint::Readme, rid, sha);
+    if let Some(hit) = ctx.cache_get(&key).await? {
+        return Ok(hit);
+    }
+    let outcome = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, _) = ctx.repo(rid)?;
+            let surf_repo = Repository::open(repo.path())?;
+            let paths = [
+                "README",
+                "README.md",
+                "README.markdown",
+                "README.txt",
+                "README.rst",
+                "README.org",
+                "Readme.md",
+            ];
+
+            for path in paths
+                .iter()
+                .map(ToString::to_string)
+                .chain(paths.iter().map(|p| p.to_lowercase()))
+            {
+                match read_blob(&repo, &surf_repo, sha, &path) {
+                    Ok(BlobData::TooLarge) => return Ok::<_, Error>(BlobOutcome::TooLarge),
+                    Ok(BlobData::Blob {
                         is_binary,
-                        &content,
-                        &path,
-                        &last_commit,
-                    )))
+                        content,
+                        last_commit,
+                    }) => {
+                        return Ok::<_, Error>(BlobOutcome::Json(api::json::commit::blob_json(
+                            is_binary,
+                            &content,
+                            &path,
+                            &last_commit,
+                        )))
+                    }
+                    Err(_) => continue,
                 }
-                Err(_) => continue,
             }
-        }
 
-        Err(Error::NotFound)
+            Err(Error::NotFound)
+        }
     })
     .await?;
 
@@ -1211,7 +1350,7 @@ async fn readme_handler(
             )
                 .into_response(),
         ),
-        BlobOutcome::Json(value) => Ok::<_, Error>(immutable_response(value).into_response()),
+        BlobOutcome::Json(value) => ctx.cache_put(key, &value).await,
     }
 }
 
@@ -1221,41 +1360,58 @@ async fn issues_handler(
     State(ctx): State<Context>,
     Path(rid): Path<String>,
     Query(qs): Query<CobsQuery<api::query::IssueStatus>>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let issues = api::blocking(move || {
-        let (repo, _) = ctx.repo(rid)?;
-        let CobsQuery {
-            page,
-            per_page,
-            status,
-        } = qs;
-        let page = page.unwrap_or(0);
-        let per_page = per_page.unwrap_or(10);
-        let status = status.unwrap_or_default();
-        let issues = ctx.profile.issues(&repo)?;
-        let mut issues: Vec<_> = issues
-            .list()?
-            .filter_map(|r| {
-                let (id, issue) = r.ok()?;
-                (status.matches(issue.state())).then_some((id, issue))
-            })
-            .collect::<Vec<_>>();
+    let key = cache::Key::new(Endpoint::Issues, rid, serde_json::to_string(&qs)?);
+    let ticket = match ctx.cache_get_tagged(key, &headers).await? {
+        Lookup::Hit(response) => return Ok(response),
+        Lookup::Miss(ticket) => ticket,
+    };
+    let (etag, issues) = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, _) = ctx.repo(rid)?;
+            let etag = api::repo_etag(&repo, &[])?;
+            if etag.matches(&headers) {
+                return Ok((etag, None));
+            }
+            let CobsQuery {
+                page,
+                per_page,
+                status,
+            } = qs;
+            let page = page.unwrap_or(0);
+            let per_page = per_page.unwrap_or(10);
+            let status = status.unwrap_or_default();
+            let issues = ctx.profile.issues(&repo)?;
+            let mut issues: Vec<_> = issues
+                .list()?
+                .filter_map(|r| {
+                    let (id, issue) = r.ok()?;
+                    (status.matches(issue.state())).then_some((id, issue))
+                })
+                .collect::<Vec<_>>();
 
-        issues.sort_by_key(|(_, b)| std::cmp::Reverse(b.timestamp()));
-        let aliases = &ctx.profile.aliases();
-        Ok::<_, Error>(
-            issues
+            issues.sort_by_key(|(_, b)| std::cmp::Reverse(b.timestamp()));
+            let aliases = &ctx.profile.aliases();
+            let issues = issues
                 .into_iter()
-                .map(|(id, issue)| api::json::cobs::Issue::new(&issue).as_json(id, aliases))
                 .skip(page * per_page)
                 .take(per_page)
-                .collect::<Vec<_>>(),
-        )
+                .map(|(id, issue)| {
+                    let mut value = api::json::cobs::Issue::new(&issue).as_json(id, aliases);
+                    api::json::link_embeds(&mut value, &rid, &id);
+                    value
+                })
+                .collect::<Vec<_>>();
+
+            Ok::<_, Error>((etag, Some(issues)))
+        }
     })
     .await?;
 
-    Ok::<_, Error>(Json(issues))
+    ctx.cache_put_tagged(ticket, etag, issues).await
 }
 
 /// Get repo issue.
@@ -1263,64 +1419,106 @@ async fn issues_handler(
 async fn issue_handler(
     State(ctx): State<Context>,
     Path((rid, issue_id)): Path<(String, Oid)>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let value = api::blocking(move || {
+    let response = api::blocking(move || {
         let (repo, _) = ctx.repo(rid)?;
+        let etag = api::repo_etag(&repo, &[])?;
+        if etag.matches(&headers) {
+            return Ok(etag.not_modified());
+        }
         let issue = ctx
             .profile
             .issues(&repo)?
             .get(&issue_id.into())?
             .ok_or(Error::NotFound)?;
         let aliases = ctx.profile.aliases();
+        let mut value = api::json::cobs::Issue::new(&issue).as_json(issue_id.into(), &aliases);
+        api::json::link_embeds(&mut value, &rid, &issue_id.into());
 
-        Ok::<_, Error>(api::json::cobs::Issue::new(&issue).as_json(issue_id.into(), &aliases))
+        Ok::<_, Error>(etag.response(value))
     })
     .await?;
 
-    Ok::<_, Error>(Json(value))
+    Ok::<_, Error>(response)
 }
 
 /// Get repo patches list.
-/// `GET /repos/:rid/patches`
+/// `GET /repos/:rid/patches?ci=<bool>`
 async fn patches_handler(
     State(ctx): State<Context>,
     Path(rid): Path<String>,
     Query(qs): Query<CobsQuery<api::query::PatchStatus>>,
+    Query(job::CiQuery { ci }): Query<job::CiQuery>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let patches = api::blocking(move || {
-        let (repo, _) = ctx.repo(rid)?;
-        let CobsQuery {
-            page,
-            per_page,
-            status,
-        } = qs;
-        let page = page.unwrap_or(0);
-        let per_page = per_page.unwrap_or(10);
-        let status = status.unwrap_or_default();
-        let patches = ctx.profile.patches(&repo)?;
-        let mut patches = patches
-            .list()?
-            .filter_map(|r| {
-                let (id, patch) = r.ok()?;
-                (status.matches(patch.state())).then_some((id, patch))
-            })
-            .collect::<Vec<_>>();
-        patches.sort_by_key(|(_, b)| std::cmp::Reverse(b.timestamp()));
-        let aliases = ctx.profile.aliases();
-        Ok::<_, Error>(
-            patches
+    // CI summaries come from job COBs, whose updates are signed refs like any
+    // other, so responses with them are cached alike.
+    let key = cache::Key::new(Endpoint::Patches, rid, serde_json::to_string(&(&qs, ci))?);
+    let ticket = match ctx.cache_get_tagged(key, &headers).await? {
+        Lookup::Hit(response) => return Ok(response),
+        Lookup::Miss(ticket) => ticket,
+    };
+    let (etag, patches) = api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let (repo, _) = ctx.repo(rid)?;
+            let etag = api::repo_etag(&repo, &[])?;
+            if etag.matches(&headers) {
+                return Ok((etag, None));
+            }
+            let CobsQuery {
+                page,
+                per_page,
+                status,
+            } = qs;
+            let page = page.unwrap_or(0);
+            let per_page = per_page.unwrap_or(10);
+            let status = status.unwrap_or_default();
+            let patches = ctx.profile.patches(&repo)?;
+            let mut patches = patches
+                .list()?
+                .filter_map(|r| {
+                    let (id, patch) = r.ok()?;
+                    (status.matches(patch.state())).then_some((id, patch))
+                })
+                .collect::<Vec<_>>();
+            patches.sort_by_key(|(_, b)| std::cmp::Reverse(b.timestamp()));
+            let aliases = ctx.profile.aliases();
+            let summaries = if ci {
+                Some(job::JobsSource::new(&ctx, rid).summaries()?)
+            } else {
+                None
+            };
+            let patches = patches
                 .into_iter()
-                .map(|(id, patch)| api::json::cobs::Patch::new(&patch).as_json(id, &repo, &aliases))
                 .skip(page * per_page)
                 .take(per_page)
-                .collect::<Vec<_>>(),
-        )
+                .map(|(id, patch)| {
+                    let mut value =
+                        api::json::cobs::Patch::new(&patch).as_json(id, &repo, &aliases);
+                    if let (Some(summaries), Some(revisions)) =
+                        (&summaries, value["revisions"].as_array_mut())
+                    {
+                        // Revisions are serialized in the same order as
+                        // `Patch::revisions` yields them.
+                        for ((_, revision), json) in patch.revisions().zip(revisions) {
+                            job::annotate(json, revision.head(), summaries);
+                        }
+                    }
+                    api::json::link_embeds(&mut value, &rid, &id);
+                    value
+                })
+                .collect::<Vec<_>>();
+
+            Ok::<_, Error>((etag, Some(patches)))
+        }
     })
     .await?;
 
-    Ok::<_, Error>(Json(patches))
+    ctx.cache_put_tagged(ticket, etag, patches).await
 }
 
 /// Get repo patch.
@@ -1328,23 +1526,27 @@ async fn patches_handler(
 async fn patch_handler(
     State(ctx): State<Context>,
     Path((rid, patch_id)): Path<(String, Oid)>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = ctx.resolve_repo(&rid)?;
-    let value = api::blocking(move || {
+    let response = api::blocking(move || {
         let (repo, _) = ctx.repo(rid)?;
+        let etag = api::repo_etag(&repo, &[])?;
+        if etag.matches(&headers) {
+            return Ok(etag.not_modified());
+        }
         let patches = ctx.profile.patches(&repo)?;
         let patch = patches.get(&patch_id.into())?.ok_or(Error::NotFound)?;
         let aliases = ctx.profile.aliases();
+        let mut value =
+            api::json::cobs::Patch::new(&patch).as_json(patch_id.into(), &repo, &aliases);
+        api::json::link_embeds(&mut value, &rid, &patch_id.into());
 
-        Ok::<_, Error>(api::json::cobs::Patch::new(&patch).as_json(
-            patch_id.into(),
-            &repo,
-            &aliases,
-        ))
+        Ok::<_, Error>(etag.response(value))
     })
     .await?;
 
-    Ok::<_, Error>(Json(value))
+    Ok::<_, Error>(response)
 }
 
 #[cfg(test)]
@@ -1354,11 +1556,77 @@ mod routes {
     use axum::extract::connect_info::MockConnectInfo;
     use axum::http::StatusCode;
     use pretty_assertions::assert_eq;
-    use radicle::storage::ReadStorage;
+    use radicle::storage::{ReadStorage, SignRepository, WriteRepository, WriteStorage};
     use serde_json::json;
 
     use crate::test::*;
 
+    #[tokio::test]
+    async fn test_repos_etag() {
+        let tmp = tempfile::tempdir().unwrap();
+        let seed = seed(tmp.path());
+        let app = super::router(seed.clone());
+        let paths = [
+            format!("/repos/{RID}"),
+            format!("/repos/{RID}/issues"),
+            format!("/repos/{RID}/issues/{ISSUE_ID}"),
+            format!("/repos/{RID}/patches"),
+            format!("/repos/{RID}/remotes"),
+            format!("/repos/{RID}/commits/{HEAD}"),
+        ];
+        let mut etags = Vec::new();
+
+        for path in &paths {
+            let response = get(&app, path).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["Cache-Control"], "public, no-cache");
+            let etag = response.headers()["ETag"].to_str().unwrap().to_owned();
+            assert!(etag.starts_with('"') && etag.ends_with('"'));
+
+            let response = get_with_headers(&app, path, &[("If-None-Match", etag.as_str())]).await;
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(response.headers()["ETag"], etag.as_str());
+            assert!(response.body().await.is_empty());
+
+            let weak = format!("\"stale\", W/{etag}");
+            let response = get_with_headers(&app, path, &[("If-None-Match", weak.as_str())]).await;
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+            let response = get_with_headers(&app, path, &[("If-None-Match", "\"stale\"")]).await;
+            assert_eq!(response.status(), StatusCode::OK);
+
+            etags.push(etag);
+        }
+
+        // Any change to a remote's signed refs changes the repo's state.
+        {
+            let signer =
+                radicle::crypto::SigningKey::from_seed(radicle::crypto::Seed::new([0xff; 32]));
+            let repo = seed
+                .profile()
+                .storage
+                .repository_mut(RID.parse().unwrap())
+                .unwrap();
+            repo.raw()
+                .reference(
+                    &format!(
+                        "refs/namespaces/{}/refs/heads/etag",
+                        seed.profile().public_key
+                    ),
+                    PARENT.parse::<radicle::git::raw::Oid>().unwrap(),
+                    false,
+                    "test: add branch",
+                )
+                .unwrap();
+            repo.sign_refs(&signer).unwrap();
+        }
+        for (path, etag) in paths.iter().zip(etags) {
+            let response = get_with_headers(&app, path, &[("If-None-Match", etag.as_str())]).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_ne!(response.headers()["ETag"], etag.as_str());
+        }
+    }
+
     #[tokio::test]
     async fn test_repos_root() {
         let tmp = tempfile::tempdir().unwrap();
@@ -1556,6 +1824,116 @@ mod routes {
         assert!(rids.contains(&"rad:z4GypKmh1gkEfmkXtarcYnkvtFUfE"));
     }
 
+    #[tokio::test]
+    async fn test_repos_root_from_summaries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let seed = seed(tmp.path());
+        let app = super::router(seed.clone())
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 8080))));
+        let paths = [
+            "/repos?show=all",
+            "/repos?show=all&sort=seeding",
+            "/repos/search?q=hello",
+        ];
+        let mut walked = Vec::new();
+        for path in paths {
+            walked.push(get(&app, path).await.json().await);
+        }
+
+        seed.summaries().refresh_all(&seed).unwrap();
+        for (path, walked) in paths.into_iter().zip(walked) {
+            let response = get(&app, path).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.json().await, walked, "{path}");
+        }
+
+        // With summaries, activity sorts aren't collapsed to rid sort.
+        let response = get(&app, "/repos?show=all&sort=activity").await;
+        let body = response.json().await;
+        let rids = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|repo| repo["rid"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        let times = rids
+            .iter()
+            .map(|rid| {
+                seed.summaries()
+                    .get(&rid.parse().unwrap())
+                    .unwrap()
+                    .head_time
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(rids.len(), 2);
+        assert!(times.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[tokio::test]
+    async fn test_repos_turned_private_are_unlisted() {
+        use radicle::cob::Title;
+        use radicle::crypto::{Seed, SigningKey};
+        use radicle::identity::{Identity, Visibility};
+        use radicle::storage::ReadRepository as _;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let seed = seed(tmp.path());
+        let app = super::router(seed.clone())
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 8080))));
+        let listed = |body: serde_json::Value| {
+            body.as_array()
+                .unwrap()
+                .iter()
+                .any(|repo| repo["rid"] == RID)
+        };
+        let summaries = seed.summaries().clone();
+        tokio::spawn(summaries.clone().run(seed.clone()));
+        while !summaries.is_ready() {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(listed(get(&app, "/repos?show=all").await.json().await));
+        assert!(listed(
+            get(&app, "/repos/search?q=hello").await.json().await
+        ));
+
+        {
+            let signer = SigningKey::from_seed(Seed::new([0xff; 32]));
+            let rid = RID.parse().unwrap();
+            let repo = seed.profile().storage.repository_mut(rid).unwrap();
+            let mut identity = Identity::load_mut(&repo, &signer).unwrap();
+            let doc = repo
+                .identity_doc()
+                .unwrap()
+                .doc
+                .with_edits(|raw| {
+                    raw.visibility = Visibility::Private {
+                        allow: Default::default(),
+                    };
+                })
+                .unwrap();
+            identity
+                .update(Title::new("Make private").unwrap(), "", &doc)
+                .unwrap();
+            let head = repo.identity_head_of(signer.public_key()).unwrap();
+            repo.set_identity_head_to(head).unwrap();
+        }
+        // The node's events about the repo are withheld from now on.
+        seed.events().withhold(RID.parse().unwrap());
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+        while listed(get(&app, "/repos?show=all").await.json().await) {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "repo is still listed"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!listed(
+            get(&app, "/repos/search?q=hello").await.json().await
+        ));
+    }
+
     #[tokio::test]
     async fn test_repos_root_sort_activity() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2063,6 +2441,33 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_repos_commits_with_ci() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+
+        let response = get(&app, format!("/repos/{RID}/commits?parent={HEAD}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("Cache-Control").is_some());
+
+        // CI summaries change over time, so the response must not be cached
+        // as immutable even though a parent commit was given.
+        let response = get(&app, format!("/repos/{RID}/commits?parent={HEAD}&ci=true")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get("Cache-Control").is_none());
+
+        // The fixture has no jobs, so no commit carries a summary.
+        let body = response.json().await;
+        let commits = body.as_array().unwrap();
+        assert!(!commits.is_empty());
+        assert!(commits.iter().all(|commit| commit.get("ci").is_none()));
+
+        let response = get(&app, format!("/repos/{RID}/patches?ci=true")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.json().await;
+        assert!(body[0]["revisions"][0].get("ci").is_none());
+    }
+
     #[tokio::test]
     async fn test_repos_commits_not_found() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2269,6 +2674,63 @@ mod routes {
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
 
+    #[tokio::test]
+    async fn test_repos_identity_revisions() {
+        use radicle::crypto::{Seed, SigningKey};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed_multi_peer(tmp.path());
+        let app =
+            super::router(ctx).layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 8080))));
+        let response = get(&app, format!("/repos/{RID}/identity/revisions")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.json().await;
+        let revisions = body.as_array().unwrap();
+        assert_eq!(revisions.len(), 2);
+
+        let root = &revisions[0];
+        assert!(root["parent"].is_null());
+        assert_eq!(root["quorum"], json!(true));
+        assert_eq!(root["changes"]["delegates"]["added"], json!([DID]));
+        assert_eq!(
+            root["changes"]["threshold"],
+            json!({ "from": null, "to": 1 })
+        );
+        assert_eq!(
+            root["changes"]["payloads"]["added"],
+            json!(["xyz.radicle.project"])
+        );
+
+        let peer2 = radicle::identity::Did::from(
+            *SigningKey::from_seed(Seed::new([0xee; 32])).public_key(),
+        );
+        let update = &revisions[1];
+        assert_eq!(update["parent"], root["id"]);
+        assert_eq!(update["title"], json!("Add second delegate and crefs"));
+        assert_eq!(update["author"]["id"], json!(DID));
+        assert_eq!(update["status"], json!("accepted"));
+        assert_eq!(update["quorum"], json!(true));
+        assert_eq!(update["signatures"][0]["id"], json!(DID));
+        assert_eq!(update["changes"]["delegates"]["added"], json!([peer2]));
+        assert_eq!(update["changes"]["delegates"]["removed"], json!([]));
+        assert!(update["changes"].get("threshold").is_none());
+        assert_eq!(
+            update["changes"]["payloads"]["added"],
+            json!(["xyz.radicle.crefs"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_identity_revisions_private() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(&app, format!("/repos/{RID_PRIVATE}/identity/revisions")).await;
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn test_repos_multi_peer_canonical_refs() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2365,6 +2827,89 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_repos_diff_cached() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let path = format!("/repos/{RID}/diff/{INITIAL_COMMIT}/{HEAD}");
+
+        let miss = get(&app, &path).await;
+        assert_eq!(miss.status(), StatusCode::OK);
+        let miss = miss.json().await;
+        let hit = get(&app, &path).await;
+        assert_eq!(hit.status(), StatusCode::OK);
+        assert_eq!(
+            hit.headers()["Cache-Control"],
+            "public, max-age=604800, immutable"
+        );
+        assert_eq!(hit.json().await, miss);
+
+        let stats = ctx.cache_stats().await.unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(
+            stats.endpoints[&crate::cache::Endpoint::Diff],
+            crate::cache::Counters {
+                hits: 1,
+                misses: 1,
+                disk_hits: 0,
+                invalidations: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repos_issues_cache_invalidation() {
+        async fn counters(cache: &crate::cache::Cache) -> crate::cache::Counters {
+            cache.stats().await.endpoints[&crate::cache::Endpoint::Issues]
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let cache = ctx.cache().unwrap().clone();
+        let path = format!("/repos/{RID}/issues?status=all");
+
+        ctx.events().set_connected(true);
+        tokio::spawn(cache.clone().follow(ctx.events().clone()));
+
+        let miss = get(&app, &path).await;
+        assert_eq!(miss.status(), StatusCode::OK);
+        let etag = miss.headers()["ETag"].to_str().unwrap().to_owned();
+        let miss = miss.json().await;
+
+        let hit = get(&app, &path).await;
+        assert_eq!(hit.status(), StatusCode::OK);
+        assert_eq!(hit.headers()["ETag"], etag.as_str());
+        assert_eq!(hit.json().await, miss);
+
+        let hit = get_with_headers(&app, &path, &[("If-None-Match", etag.as_str())]).await;
+        assert_eq!(hit.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(counters(&cache).await.hits, 2);
+
+        ctx.events().publish(
+            RID.parse().unwrap(),
+            json!({ "type": "refsFetched", "rid": RID }),
+        );
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while counters(&cache).await.invalidations == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        get(&app, &path).await;
+        assert_eq!(counters(&cache).await.misses, 2);
+
+        // Without node events, nothing tells us when to invalidate.
+        ctx.events().set_connected(false);
+        get(&app, &path).await;
+        get(&app, &path).await;
+        assert_eq!(counters(&cache).await.hits, 2);
+        assert_eq!(counters(&cache).await.misses, 2);
+    }
+
     #[tokio::test]
     async fn test_repos_diff() {
         let tmp = tempfile::tempdir().unwrap();
@@ -2709,6 +3254,69 @@ mod routes {
         );
     }
 
+    #[tokio::test]
+    async fn test_repos_patch_mergeability() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+        let response = get(
+            &app,
+            format!("/repos/{RID}/patches/{PATCH_ID}/mergeability"),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.json().await,
+            json!({
+                "target": HEAD,
+                "revisions": [
+                    {
+                        "id": PATCH_ID,
+                        "head": HEAD,
+                        "status": "clean",
+                    },
+                ],
+            })
+        );
+
+        let response = get(
+            &app,
+            format!("/repos/{RID_PRIVATE}/patches/{PATCH_ID}/mergeability"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_repos_feeds() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(seed(tmp.path()));
+
+        let response = get(&app, format!("/repos/{RID}/feeds/commits.atom")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("Content-Type").unwrap(),
+            "application/atom+xml; charset=utf-8"
+        );
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains(&format!("<id>{RID}/feeds/commits</id>")));
+        assert!(body.contains(&format!("<id>{RID}/commits/{HEAD}</id>")));
+        assert_eq!(body.matches("<entry>").count(), 3);
+
+        let response = get(&app, format!("/repos/{RID}/feeds/issues.atom")).await;
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains(&format!("<id>{RID}/issues/{ISSUE_ID}</id>")));
+        assert!(body.contains("<title>Issue #1</title>"));
+
+        let response = get(&app, format!("/repos/{RID}/feeds/patches.atom")).await;
+        let body = String::from_utf8(response.body().await.to_vec()).unwrap();
+        assert!(body.contains(&format!("<id>{RID}/patches/{PATCH_ID}</id>")));
+        assert!(body.contains("<title>A new `hello world`</title>"));
+
+        let response = get(&app, format!("/repos/{RID_PRIVATE}/feeds/issues.atom")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn test_repos_private() {
         let tmp = tempfile::tempdir().unwrap();
@@ -0,0 +1,332 @@
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum::routing::patch;
+use axum::{Json, Router};
+use radicle_surf::Oid;
+use serde::Deserialize;
+
+use radicle::cob::issue::cache::Issues as _;
+use radicle::cob::patch::cache::Patches as _;
+use radicle::cob::patch::RevisionId;
+use radicle::cob::thread::CommentId;
+use radicle::cob::{issue, Label, Reaction};
+
+use crate::api;
+use crate::api::error::Error;
+use crate::api::Context;
+use crate::axum_extra::Path;
+
+/// Routes of the authenticated write API. Only mounted when httpd runs with
+/// `--write`.
+pub fn router(ctx: Context) -> Router {
+    Router::new()
+        .route("/repos/{rid}/issues/{id}", patch(issue_update_handler))
+        .route("/repos/{rid}/patches/{id}", patch(patch_update_handler))
+        .with_state(ctx)
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum CloseReason {
+    Solved,
+    #[default]
+    Other,
+}
+
+impl From<CloseReason> for issue::CloseReason {
+    fn from(reason: CloseReason) -> Self {
+        match reason {
+            CloseReason::Solved => Self::Solved,
+            CloseReason::Other => Self::Other,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum IssueAction {
+    /// Comment on the issue, in reply to its description by default.
+    Comment {
+        body: String,
+        reply_to: Option<CommentId>,
+    },
+    /// Add or remove a reaction on a comment.
+    React {
+        comment: CommentId,
+        reaction: Reaction,
+        active: bool,
+    },
+    /// Replace the issue's labels.
+    Label { labels: Vec<Label> },
+    /// Close the issue.
+    Close {
+        #[serde(default)]
+        reason: CloseReason,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+enum PatchAction {
+    /// Comment on a revision, the latest one by default.
+    Comment {
+        revision: Option<RevisionId>,
+        body: String,
+        reply_to: Option<CommentId>,
+    },
+    /// Add or remove a reaction on a revision, or on one of its comments.
+    React {
+        revision: Option<RevisionId>,
+        comment: Option<CommentId>,
+        reaction: Reaction,
+        active: bool,
+    },
+    /// Replace the patch's labels.
+    Label { labels: Vec<Label> },
+    /// Close the patch by archiving it.
+    Close,
+}
+
+/// Update an issue on behalf of the session's identity.
+/// `PATCH /repos/:rid/issues/:id`
+async fn issue_update_handler(
+    State(ctx): State<Context>,
+    Path((rid, issue_id)): Path<(String, Oid)>,
+    headers: HeaderMap,
+    Json(action): Json<IssueAction>,
+) -> impl IntoResponse {
+    ctx.sessions.authenticate(&headers).await?;
+    let rid = ctx.resolve_repo(&rid)?;
+    let cache = ctx.cache().cloned();
+    let value = api::blocking(move || {
+        let (repo, _) = ctx.repo(rid)?;
+        let signer = ctx.profile.signer()?;
+        let mut issues = ctx.profile.issues_mut(&repo, &signer)?;
+        let mut issue = issues.get_mut(&issue_id.into())?;
+
+        match action {
+            IssueAction::Comment { body, reply_to } => {
+                let reply_to = reply_to.unwrap_or_else(|| *issue.root().0);
+                issue.comment(body, reply_to, vec![])?;
+            }
+            IssueAction::React {
+                comment,
+                reaction,
+                active,
+            } => {
+                issue.react(comment, reaction, active)?;
+            }
+            IssueAction::Label { labels } => {
+                issue.label(labels)?;
+            }
+            IssueAction::Close { reason } => {
+                issue.lifecycle(issue::State::Closed {
+                    reason: reason.into(),
+                })?;
+            }
+        }
+        let aliases = ctx.profile.aliases();
+        let mut value = api::json::cobs::Issue::new(&issue).as_json(issue_id.into(), &aliases);
+        api::json::link_embeds(&mut value, &rid, &issue_id.into());
+        ctx.summaries().refresh(&ctx, rid);
+
+        Ok::<_, Error>(value)
+    })
+    .await?;
+    // Unlike fetches, changes made here aren't reported by the node.
+    if let Some(cache) = cache {
+        cache.invalidate(Some(rid)).await;
+    }
+
+    Ok::<_, Error>(Json(value))
+}
+
+/// Update a patch on behalf of the session's identity.
+/// `PATCH /repos/:rid/patches/:id`
+async fn patch_update_handler(
+    State(ctx): State<Context>,
+    Path((rid, patch_id)): Path<(String, Oid)>,
+    headers: HeaderMap,
+    Json(action): Json<PatchAction>,
+) -> impl IntoResponse {
+    ctx.sessions.authenticate(&headers).await?;
+    let rid = ctx.resolve_repo(&rid)?;
+    let cache = ctx.cache().cloned();
+    let value = api::blocking(move || {
+        let (repo, _) = ctx.repo(rid)?;
+        let signer = ctx.profile.signer()?;
+        let mut patches = ctx.profile.patches_mut(&repo, &signer)?;
+        let mut patch = patches.get_mut(&patch_id.into())?;
+        let latest = patch.latest().0;
+
+        match action {
+            PatchAction::Comment {
+                revision,
+                body,
+                reply_to,
+            } => {
+                let revision = revision.unwrap_or(latest);
+                patch.comment(revision, body, reply_to, None, vec![])?;
+            }
+            PatchAction::React {
+                revision,
+                comment: None,
+                reaction,
+                active,
+            } => {
+                let revision = revision.unwrap_or(latest);
+                patch.react(revision, reaction, None, active)?;
+            }
+            PatchAction::React {
+                revision,
+                comment: Some(comment),
+                reaction,
+                active,
+            } => {
+                let revision = revision.unwrap_or(latest);
+                patch.comment_react(revision, comment, reaction, active)?;
+            }
+            PatchAction::Label { labels } => {
+                patch.label(labels)?;
+            }
+            PatchAction::Close => {
+                patch.archive()?;
+            }
+        }
+        let aliases = ctx.profile.aliases();
+        let mut value =
+            api::json::cobs::Patch::new(&patch).as_json(patch_id.into(), &repo, &aliases);
+        api::json::link_embeds(&mut value, &rid, &patch_id.into());
+        ctx.summaries().refresh(&ctx, rid);
+
+        Ok::<_, Error>(value)
+    })
+    .await?;
+    // Unlike fetches, changes made here aren't reported by the node.
+    if let Some(cache) = cache {
+        cache.invalidate(Some(rid)).await;
+    }
+
+    Ok::<_, Error>(Json(value))
+}
+
+#[cfg(test)]
+mod routes {
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use radicle::crypto::{Seed, Signer, SigningKey};
+    use serde_json::json;
+
+    use crate::api::Context;
+    use crate::test::{self, patch, ISSUE_ID, PATCH_ID, RID};
+
+    /// Open and authorize a session for the seed profile's identity.
+    async fn session(ctx: &Context) -> String {
+        let signer = SigningKey::from_seed(Seed::new([0xff; 32]));
+        let (id, session) = ctx.sessions.create(ctx.profile().did()).await;
+        ctx.sessions
+            .authorize(&id, &signer.sign(session.challenge.as_bytes()))
+            .await
+            .unwrap();
+
+        id.to_string()
+    }
+
+    fn body(value: serde_json::Value) -> Option<Body> {
+        Some(Body::from(value.to_string()))
+    }
+
+    #[tokio::test]
+    async fn test_issue_update() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let path = format!("/repos/{RID}/issues/{ISSUE_ID}");
+        let comment = json!({ "type": "comment", "body": "Triaged." });
+
+        let response = patch(&app, &path, body(comment.clone()), None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let token = session(&ctx).await;
+        let response = patch(&app, &path, body(comment), Some(token.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let issue = response.json().await;
+        let discussion = issue["discussion"].as_array().unwrap();
+        assert_eq!(discussion.last().unwrap()["body"], "Triaged.");
+
+        let response = patch(
+            &app,
+            &path,
+            body(json!({ "type": "label", "labels": ["bug"] })),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.json().await["labels"], json!(["bug"]));
+
+        let response = patch(
+            &app,
+            &path,
+            body(json!({ "type": "close", "reason": "solved" })),
+            Some(token),
+        )
+        .await;
+        assert_eq!(
+            response.json().await["state"],
+            json!({ "status": "closed", "reason": "solved" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_patch_update() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(ctx.clone());
+        let path = format!("/repos/{RID}/patches/{PATCH_ID}");
+        let token = session(&ctx).await;
+
+        let response = patch(
+            &app,
+            &path,
+            body(json!({ "type": "comment", "body": "Looks good." })),
+            Some(token.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let value = response.json().await;
+        let discussions = value["revisions"][0]["discussions"].as_array().unwrap();
+        assert_eq!(discussions.last().unwrap()["body"], "Looks good.");
+
+        let response = patch(&app, &path, body(json!({ "type": "close" })), Some(token)).await;
+        assert_eq!(
+            response.json().await["state"],
+            json!({ "status": "archived" })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_api_is_opt_in() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut ctx = test::seed(tmp.path());
+        let token = session(&ctx).await;
+        let path = format!("/repos/{RID}/issues/{ISSUE_ID}");
+        let comment = json!({ "type": "comment", "body": "Triaged." });
+
+        let app = crate::api::v1::repos::router(ctx.clone());
+        let response = patch(&app, &path, body(comment.clone()), Some(token.clone())).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+        ctx.set_write(true);
+        let app = crate::api::v1::repos::router(ctx);
+        let response = patch(&app, &path, body(comment), Some(token)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,296 @@
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+use chrono::{DateTime, SecondsFormat};
+use radicle_surf::Repository;
+
+use radicle::cob::{issue::cache::Issues as _, patch::cache::Patches as _};
+use radicle::identity::{Did, Doc, RepoId};
+use radicle::node::AliasStore;
+use radicle::storage::ReadRepository;
+
+use crate::api;
+use crate::api::error::Error;
+use crate::api::xml::escape;
+use crate::api::Context;
+use crate::axum_extra::Path;
+
+/// Maximum number of entries in a feed.
+const FEED_SIZE: usize = 30;
+
+/// A single Atom feed entry.
+struct Entry {
+    /// A stable IRI identifying the entry, e.g. `rad:z3gq…/issues/<id>`.
+    id: String,
+    title: String,
+    author: String,
+    published: i64,
+    updated: i64,
+    /// Path of the entry's JSON API resource.
+    link: String,
+    summary: String,
+}
+
+/// An Atom feed, see <https://www.rfc-editor.org/rfc/rfc4287>.
+struct Feed {
+    id: String,
+    title: String,
+    /// Path of the feed itself.
+    link: String,
+    entries: Vec<Entry>,
+}
+
+impl Feed {
+    fn render(&self) -> String {
+        let updated = self
+            .entries
+            .iter()
+            .map(|e| e.updated)
+            .max()
+            .unwrap_or_default();
+        let mut xml = String::from(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+
+        xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+        xml.push_str(&format!("<id>{}</id>", escape(&self.id)));
+        xml.push_str(&format!("<title>{}</title>", escape(&self.title)));
+        xml.push_str(&format!("<updated>{}</updated>", rfc3339(updated)));
+        xml.push_str(&format!(
+            r#"<link rel="self" href="{}"/>"#,
+            escape(&self.link)
+        ));
+        for entry in &self.entries {
+            xml.push_str("<entry>");
+            xml.push_str(&format!("<id>{}</id>", escape(&entry.id)));
+            xml.push_str(&format!("<title>{}</title>", escape(&entry.title)));
+            xml.push_str(&format!(
+                "<author><name>{}</name></author>",
+                escape(&entry.author)
+            ));
+            xml.push_str(&format!(
+                "<published>{}</published>",
+                rfc3339(entry.published)
+            ));
+            xml.push_str(&format!("<updated>{}</updated>", rfc3339(entry.updated)));
+            xml.push_str(&format!(
+                r#"<link rel="alternate" type="application/json" href="{}"/>"#,
+                escape(&entry.link)
+            ));
+            if !entry.summary.is_empty() {
+                xml.push_str(&format!(
+                    r#"<summary type="text">{}</summary>"#,
+                    escape(&entry.summary)
+                ));
+            }
+            xml.push_str("</entry>");
+        }
+        xml.push_str("</feed>");
+
+        xml
+    }
+}
+
+impl IntoResponse for Feed {
+    fn into_response(self) -> axum::response::Response {
+        (
+            [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+            self.render(),
+        )
+            .into_response()
+    }
+}
+
+fn rfc3339(secs: i64) -> String {
+    DateTime::from_timestamp(secs, 0)
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn author(did: &Did, aliases: &impl AliasStore) -> String {
+    aliases
+        .alias(did)
+        .map_or_else(|| did.to_string(), |alias| alias.to_string())
+}
+
+fn feed_title(doc: &Doc, rid: &RepoId, kind: &str) -> String {
+    match doc.project() {
+        Ok(project) => format!("{}: {kind}", project.name()),
+        Err(_) => format!("{rid}: {kind}"),
+    }
+}
+
+/// Feed of the latest commits on the canonical default branch.
+/// `GET /repos/:rid/feeds/commits.atom`
+pub async fn commits_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<String>,
+) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let feed = api::blocking(move || {
+        let (repo, doc) = ctx.repo(rid)?;
+        let (_, head) = repo.head()?;
+        let repo = Repository::open(repo.path())?;
+        let entries = repo
+            .history(&head.to_string())?
+            .take(FEED_SIZE)
+            .filter_map(|commit| {
+                let commit = commit.ok()?;
+                let time = commit.committer.time.seconds();
+
+                Some(Entry {
+                    id: format!("{rid}/commits/{}", commit.id),
+                    title: commit.summary.clone(),
+                    author: commit.author.name.clone(),
+                    published: commit.author.time.seconds(),
+                    updated: time,
+                    link: format!("/api/v1/repos/{rid}/commits/{}", commit.id),
+                    summary: commit.description().trim().to_owned(),
+                })
+            })
+            .collect();
+
+        Ok::<_, Error>(Feed {
+            id: format!("{rid}/feeds/commits"),
+            title: feed_title(&doc.doc, &rid, "commits"),
+            link: format!("/api/v1/repos/{rid}/feeds/commits.atom"),
+            entries,
+        })
+    })
+    .await?;
+
+    Ok::<_, Error>(feed)
+}
+
+/// Feed of the most recently active issues, in any state.
+/// `GET /repos/:rid/feeds/issues.atom`
+pub async fn issues_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<String>,
+) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let feed = api::blocking(move || {
+        let (repo, doc) = ctx.repo(rid)?;
+        let aliases = ctx.profile.aliases();
+        let mut entries = ctx
+            .profile
+            .issues(&repo)?
+            .list()?
+            .filter_map(|r| {
+                let (id, issue) = r.ok()?;
+                let published = issue.timestamp().as_secs() as i64;
+                let updated = issue
+                    .comments()
+                    .map(|(_, c)| c.timestamp().as_secs() as i64)
+                    .max()
+                    .unwrap_or(published);
+
+                Some(Entry {
+                    id: format!("{rid}/issues/{id}"),
+                    title: issue.title().to_owned(),
+                    author: author(issue.author().id(), &aliases),
+                    published,
+                    updated,
+                    link: format!("/api/v1/repos/{rid}/issues/{id}"),
+                    summary: issue.description().to_owned(),
+                })
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.updated));
+        entries.truncate(FEED_SIZE);
+
+        Ok::<_, Error>(Feed {
+            id: format!("{rid}/feeds/issues"),
+            title: feed_title(&doc.doc, &rid, "issues"),
+            link: format!("/api/v1/repos/{rid}/feeds/issues.atom"),
+            entries,
+        })
+    })
+    .await?;
+
+    Ok::<_, Error>(feed)
+}
+
+/// Feed of the most recently active patches, in any state.
+/// `GET /repos/:rid/feeds/patches.atom`
+pub async fn patches_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<String>,
+) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let feed = api::blocking(move || {
+        let (repo, doc) = ctx.repo(rid)?;
+        let aliases = ctx.profile.aliases();
+        let mut entries = ctx
+            .profile
+            .patches(&repo)?
+            .list()?
+            .filter_map(|r| {
+                let (id, patch) = r.ok()?;
+                let published = patch.timestamp().as_secs() as i64;
+                let updated = patch
+                    .revisions()
+                    .flat_map(|(_, revision)| {
+                        std::iter::once(revision.timestamp().as_secs()).chain(
+                            revision
+                                .discussion()
+                                .comments()
+                                .map(|(_, c)| c.timestamp().as_secs()),
+                        )
+                    })
+                    .max()
+                    .map_or(published, |t| t as i64);
+                let (_, latest) = patch.latest();
+
+                Some(Entry {
+                    id: format!("{rid}/patches/{id}"),
+                    title: patch.title().to_owned(),
+                    author: author(patch.author().id(), &aliases),
+                    published,
+                    updated,
+                    link: format!("/api/v1/repos/{rid}/patches/{id}"),
+                    summary: latest.description().to_owned(),
+                })
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.updated));
+        entries.truncate(FEED_SIZE);
+
+        Ok::<_, Error>(Feed {
+            id: format!("{rid}/feeds/patches"),
+            title: feed_title(&doc.doc, &rid, "patches"),
+            link: format!("/api/v1/repos/{rid}/feeds/patches.atom"),
+            entries,
+        })
+    })
+    .await?;
+
+    Ok::<_, Error>(feed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feeds_are_escaped() {
+        let feed = Feed {
+            id: "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/feeds/issues".to_owned(),
+            title: "hello-world: issues".to_owned(),
+            link: "/api/v1/repos/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/feeds/issues.atom".to_owned(),
+            entries: vec![Entry {
+                id: "rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/issues/1".to_owned(),
+                title: "<script> & friends".to_owned(),
+                author: "seed".to_owned(),
+                published: 1671125284,
+                updated: 1671125300,
+                link: "/api/v1/repos/rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp/issues/1".to_owned(),
+                summary: String::new(),
+            }],
+        };
+        let xml = feed.render();
+
+        assert!(xml.contains("<title>&lt;script&gt; &amp; friends</title>"));
+        assert!(xml.contains("<published>2022-12-15T17:28:04Z</published>"));
+        assert!(xml.contains("<updated>2022-12-15T17:28:20Z</updated>"));
+        assert!(!xml.contains("<summary"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeSet;
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+use serde_json::Value;
+
+use radicle::cob::identity::{Revision, RevisionId, State as RevisionState};
+use radicle::identity::doc::PayloadId;
+use radicle::identity::{Did, Doc, Identity, Visibility};
+use radicle::node::AliasStore;
+
+use crate::api::error::Error;
+use crate::api::json::Author;
+use crate::api::Context;
+use crate::axum_extra::Path;
+
+/// A field of the identity document that changed between a revision and its
+/// parent. `from` is `None` for the root revision, which has no parent.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct Change<T> {
+    from: Option<T>,
+    to: T,
+}
+
+#[derive(Default, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct DelegateChanges {
+    added: Vec<Did>,
+    removed: Vec<Did>,
+}
+
+#[derive(Default, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct PayloadChanges {
+    added: Vec<PayloadId>,
+    removed: Vec<PayloadId>,
+    modified: Vec<PayloadId>,
+}
+
+/// The differences between a revision's document and its parent's.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct Changes {
+    delegates: DelegateChanges,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    threshold: Option<Change<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    visibility: Option<Change<Visibility>>,
+    payloads: PayloadChanges,
+}
+
+impl Changes {
+    /// Compare `doc` against the document of its parent revision, if any.
+    fn between(parent: Option<&Doc>, doc: &Doc) -> Self {
+        let old_delegates = parent
+            .map(|p| p.delegates().iter().copied().collect::<BTreeSet<_>>())
+            .unwrap_or_default();
+        let new_delegates = doc.delegates().iter().copied().collect::<BTreeSet<_>>();
+        let delegates = DelegateChanges {
+            added: new_delegates.difference(&old_delegates).copied().collect(),
+            removed: old_delegates.difference(&new_delegates).copied().collect(),
+        };
+
+        let threshold = match parent.map(|p| p.threshold()) {
+            Some(from) if from == doc.threshold() => None,
+            from => Some(Change {
+                from,
+                to: doc.threshold(),
+            }),
+        };
+        let visibility = match parent.map(|p| p.visibility()) {
+            Some(from) if from == doc.visibility() => None,
+            from => Some(Change {
+                from: from.cloned(),
+                to: doc.visibility().clone(),
+            }),
+        };
+
+        let mut payloads = PayloadChanges::default();
+        for (id, payload) in doc.payload() {
+            match parent.and_then(|p| p.payload().get(id)) {
+                None => payloads.added.push(id.clone()),
+                Some(old) if old != payload => payloads.modified.push(id.clone()),
+                Some(_) => {}
+            }
+        }
+        if let Some(parent) = parent {
+            payloads.removed = parent
+                .payload()
+                .keys()
+                .filter(|id| !doc.payload().contains_key(*id))
+                .cloned()
+                .collect();
+        }
+
+        Self {
+            delegates,
+            threshold,
+            visibility,
+            payloads,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+enum Status {
+    Active,
+    Accepted,
+    Rejected,
+    Stale,
+}
+
+impl From<&RevisionState> for Status {
+    fn from(value: &RevisionState) -> Self {
+        match value {
+            RevisionState::Active => Self::Active,
+            RevisionState::Accepted => Self::Accepted,
+            RevisionState::Rejected => Self::Rejected,
+            RevisionState::Stale => Self::Stale,
+        }
+    }
+}
+
+/// A single revision of the repository identity document.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IdentityRevision {
+    id: RevisionId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<RevisionId>,
+    title: String,
+    description: String,
+    author: Value,
+    timestamp: u64,
+    status: Status,
+    /// Whether the revision gathered enough delegate signatures to become the
+    /// current document.
+    quorum: bool,
+    /// The delegates that signed, i.e. accepted, the revision.
+    signatures: Vec<Value>,
+    /// The delegates that rejected the revision.
+    rejections: Vec<Value>,
+    changes: Changes,
+}
+
+impl IdentityRevision {
+    fn new(revision: &Revision, parent: Option<&Revision>, aliases: &impl AliasStore) -> Self {
+        let status = Status::from(&revision.state);
+
+        Self {
+            id: revision.id,
+            parent: revision.parent,
+            title: revision.title.to_string(),
+            description: revision.description.clone(),
+            author: Author::new(revision.author.id()).as_json(aliases),
+            timestamp: revision.timestamp.as_secs(),
+            status,
+            quorum: status == Status::Accepted,
+            signatures: revision
+                .accepted()
+                .map(|did| Author::new(&Did::from(did)).as_json(aliases))
+                .collect(),
+            rejections: revision
+                .rejected()
+                .map(|did| Author::new(&Did::from(did)).as_json(aliases))
+                .collect(),
+            changes: Changes::between(parent.map(|p| p.doc()), revision.doc()),
+        }
+    }
+}
+
+/// Get the revision history of the repo identity document, oldest first.
+/// `GET /repos/:rid/identity/revisions`
+pub async fn revisions_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<String>,
+) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let revisions = crate::api::blocking(move || {
+        let (repo, _) = ctx.repo(rid)?;
+        let identity = Identity::load(&repo)?;
+        let aliases = ctx.profile.aliases();
+
+        let revisions = identity
+            .revisions()
+            .map(|revision| {
+                let parent = revision.parent.and_then(|id| identity.revision(&id));
+                IdentityRevision::new(revision, parent, &aliases)
+            })
+            .collect::<Vec<_>>();
+
+        Ok::<_, Error>(revisions)
+    })
+    .await?;
+
+    Ok::<_, Error>(Json(revisions))
+}
@@ -1,7 +1,10 @@
+use std::collections::HashMap;
+
 use axum::extract::State;
 use axum::response::IntoResponse;
 use axum::Json;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use url::Url;
 use uuid::Uuid;
 
@@ -11,8 +14,9 @@ use radicle::node::{Alias, AliasStore};
 use radicle_job::JobId;
 
 use crate::api::error::Error as ApiError;
+use crate::api::query::MAX_PER_PAGE;
 use crate::api::Context;
-use crate::axum_extra::Path;
+use crate::axum_extra::{Path, Query};
 
 #[derive(Clone, Serialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -39,6 +43,7 @@ impl Job {
                     },
                     status: (*run.status()).into(),
                     log: run.log().clone(),
+                    timestamp: run.timestamp().as_secs(),
                 })
             })
             .collect();
@@ -49,6 +54,11 @@ impl Job {
             runs,
         }
     }
+
+    /// The time of the most recent run of this job.
+    fn latest(&self) -> u64 {
+        self.runs.iter().map(|run| run.timestamp).max().unwrap_or(0)
+    }
 }
 
 #[derive(Clone, Serialize, Debug)]
@@ -66,11 +76,12 @@ struct Run {
     node: JobAuthor,
     status: Status,
     log: Url,
+    timestamp: u64,
 }
 
-#[derive(Clone, Copy, Serialize, Debug)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-enum Status {
+pub enum Status {
     Started,
     Failed,
     Succeeded,
@@ -86,9 +97,66 @@ impl From<radicle_job::Status> for Status {
     }
 }
 
+/// Aggregated run counts of all jobs for a single commit.
+#[derive(Clone, Copy, Default, Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    running: usize,
+    failed: usize,
+    succeeded: usize,
+}
+
+impl Summary {
+    fn add(&mut self, status: Status) {
+        match status {
+            Status::Started => self.running += 1,
+            Status::Failed => self.failed += 1,
+            Status::Succeeded => self.succeeded += 1,
+        }
+    }
+}
+
+/// Insert the CI summary for `commit` into the JSON object `value` under the
+/// `ci` key. Commits without any job runs are left untouched.
+pub fn annotate(value: &mut Value, commit: Oid, summaries: &HashMap<Oid, Summary>) {
+    if let (Some(summary), Some(object)) = (summaries.get(&commit), value.as_object_mut()) {
+        object.insert("ci".to_owned(), serde_json::json!(summary));
+    }
+}
+
+/// Query string to opt into CI summaries on listings. Opt-in, since run
+/// statuses change over time and would otherwise make immutable responses
+/// stale.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CiQuery {
+    #[serde(default)]
+    pub ci: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct JobsQuery {
+    pub page: Option<usize>,
+    pub per_page: Option<usize>,
+    /// Only include runs with this status.
+    pub status: Option<Status>,
+    /// Only include runs reported by this CI node.
+    pub node: Option<Did>,
+}
+
+impl JobsQuery {
+    fn matches(&self, run: &Run) -> bool {
+        self.status.is_none_or(|status| status == run.status)
+            && self.node.is_none_or(|node| node == run.node.id)
+    }
+}
+
 pub trait FindJobs {
     fn find_by_commit(&self, oid: Oid) -> Result<Vec<(JobId, radicle_job::Job)>, ApiError>;
 
+    fn find_all(&self) -> Result<Vec<(JobId, radicle_job::Job)>, ApiError>;
+
     fn jobs_by_commit<A: AliasStore>(
         &self,
         commit: Oid,
@@ -104,6 +172,47 @@ pub trait FindJobs {
 
         Ok(jobs)
     }
+
+    /// Aggregate the runs of all jobs into a [`Summary`] per commit. The job
+    /// store is read once, rather than once per commit via
+    /// [`FindJobs::find_by_commit`], since listings summarize many commits.
+    fn summaries(&self) -> Result<HashMap<Oid, Summary>, ApiError> {
+        let mut summaries = HashMap::<Oid, Summary>::new();
+        for (_, job) in self.find_all()? {
+            let summary = summaries.entry(*job.oid()).or_default();
+            for (_, runs) in job.runs().iter() {
+                for (_, run) in runs.iter() {
+                    summary.add((*run.status()).into());
+                }
+            }
+        }
+        summaries.retain(|_, summary| *summary != Summary::default());
+
+        Ok(summaries)
+    }
+
+    /// List the jobs of all commits, newest first. Runs that don't match
+    /// the query filters are dropped, as are jobs left without any runs.
+    fn jobs<A: AliasStore>(&self, query: &JobsQuery, aliases: &A) -> Result<Vec<Job>, ApiError> {
+        let page = query.page.unwrap_or(0);
+        let per_page = query.per_page.unwrap_or(10).min(MAX_PER_PAGE);
+        let mut jobs: Vec<Job> = self
+            .find_all()?
+            .into_iter()
+            .filter_map(|(id, job)| {
+                let mut job = Job::new(id, &job, aliases);
+                job.runs.retain(|run| query.matches(run));
+                (!job.runs.is_empty()).then_some(job)
+            })
+            .collect();
+        jobs.sort_by(|a, b| b.latest().cmp(&a.latest()).then(a.job_id.cmp(&b.job_id)));
+
+        Ok(jobs
+            .into_iter()
+            .skip(page * per_page)
+            .take(per_page)
+            .collect())
+    }
 }
 
 pub struct JobsSource<'a> {
@@ -111,6 +220,12 @@ pub struct JobsSource<'a> {
     rid: RepoId,
 }
 
+impl<'a> JobsSource<'a> {
+    pub fn new(ctx: &'a Context, rid: RepoId) -> Self {
+        Self { ctx, rid }
+    }
+}
+
 impl FindJobs for JobsSource<'_> {
     fn find_by_commit(&self, oid: Oid) -> Result<Vec<(JobId, radicle_job::Job)>, ApiError> {
         let (repo, _) = self.ctx.repo(self.rid)?;
@@ -119,6 +234,30 @@ impl FindJobs for JobsSource<'_> {
 
         Ok(iter.collect::<Result<_, _>>()?)
     }
+
+    fn find_all(&self) -> Result<Vec<(JobId, radicle_job::Job)>, ApiError> {
+        let (repo, _) = self.ctx.repo(self.rid)?;
+        let store = radicle_job::Jobs::open(&repo, radicle::cob::store::access::ReadOnly)?;
+
+        Ok(store.all()?.collect::<Result<_, _>>()?)
+    }
+}
+
+/// List the jobs of a repo across all commits, newest first.
+/// `GET /repos/:rid/jobs?status=<status>&node=<did>`
+pub async fn list_handler(
+    State(ctx): State<Context>,
+    Path(rid): Path<String>,
+    Query(qs): Query<JobsQuery>,
+) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let jobs = crate::api::blocking(move || {
+        let aliases = ctx.profile.aliases();
+        JobsSource::new(&ctx, rid).jobs(&qs, &aliases)
+    })
+    .await?;
+
+    Ok::<_, ApiError>(Json(jobs))
 }
 
 /// Get jobs for a commit.
@@ -130,7 +269,7 @@ pub async fn handler(
     let rid = ctx.resolve_repo(&rid)?;
     let jobs = crate::api::blocking(move || {
         let aliases = ctx.profile.aliases();
-        JobsSource { ctx: &ctx, rid }.jobs_by_commit(sha, &aliases)
+        JobsSource::new(&ctx, rid).jobs_by_commit(sha, &aliases)
     })
     .await?;
 
@@ -139,7 +278,6 @@ pub async fn handler(
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
     use std::str::FromStr;
 
     use radicle::node::NodeId;
@@ -153,6 +291,10 @@ mod tests {
         fn find_by_commit(&self, _oid: Oid) -> Result<Vec<(JobId, radicle_job::Job)>, ApiError> {
             Ok(self.0.clone())
         }
+
+        fn find_all(&self) -> Result<Vec<(JobId, radicle_job::Job)>, ApiError> {
+            Ok(self.0.clone())
+        }
     }
 
     fn job(job_id: &str, with_run: bool) -> (JobId, radicle_job::Job) {
@@ -176,6 +318,151 @@ mod tests {
         (JobId::from_str(job_id).unwrap(), job)
     }
 
+    /// A job with a single run by `node`, reported at `timestamp`.
+    fn job_with_run(
+        job_id: &str,
+        node: &str,
+        status: serde_json::Value,
+        timestamp: u64,
+    ) -> (JobId, radicle_job::Job) {
+        let job: radicle_job::Job = serde_json::from_value(serde_json::json!({
+            "oid": "e8c676b9e3b42308dc9d218b70faa5408f8e58ca",
+            "runs": {
+                node: {
+                    "f1ad9c19-6f9c-4f1a-9a9e-1d0d3e7c1c2f": {
+                        "status": status,
+                        "log": "https://example.com/log",
+                        "timestamp": timestamp,
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        (JobId::from_str(job_id).unwrap(), job)
+    }
+
+    const NODE_A: &str = "z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi";
+    const NODE_B: &str = "z6MksFqXN3Yhqk8pTJdUGLwATkRfQvwZXPqR2qMEhbS9wzpT";
+
+    fn stub_jobs() -> StubJobs {
+        StubJobs(vec![
+            job_with_run(
+                "a1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+                NODE_A,
+                serde_json::json!("Started"),
+                100,
+            ),
+            job_with_run(
+                "b1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+                NODE_B,
+                serde_json::json!({ "Finished": "Failed" }),
+                300,
+            ),
+            job_with_run(
+                "c1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+                NODE_A,
+                serde_json::json!({ "Finished": "Succeeded" }),
+                200,
+            ),
+            job("e8c676b9e3b42308dc9d218b70faa5408f8e58ca", false),
+        ])
+    }
+
+    fn ids(jobs: &[Job]) -> Vec<String> {
+        jobs.iter().map(|job| job.job_id.to_string()).collect()
+    }
+
+    #[test]
+    fn jobs_are_listed_newest_first() {
+        let aliases: HashMap<NodeId, Alias> = HashMap::new();
+        let jobs = stub_jobs().jobs(&JobsQuery::default(), &aliases).unwrap();
+
+        assert_eq!(
+            ids(&jobs),
+            vec![
+                "b1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+                "c1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+                "a1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+            ]
+        );
+    }
+
+    #[test]
+    fn jobs_are_filtered_by_status_and_node() {
+        let aliases: HashMap<NodeId, Alias> = HashMap::new();
+        let source = stub_jobs();
+
+        let query = JobsQuery {
+            status: Some(Status::Failed),
+            ..JobsQuery::default()
+        };
+        let jobs = source.jobs(&query, &aliases).unwrap();
+        assert_eq!(ids(&jobs), vec!["b1b2c3d4e5f6071829304a5b6c7d8e9f00112233"]);
+
+        let query = JobsQuery {
+            node: Some(Did::from_str(&format!("did:key:{NODE_A}")).unwrap()),
+            ..JobsQuery::default()
+        };
+        let jobs = source.jobs(&query, &aliases).unwrap();
+        assert_eq!(
+            ids(&jobs),
+            vec![
+                "c1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+                "a1b2c3d4e5f6071829304a5b6c7d8e9f00112233",
+            ]
+        );
+    }
+
+    #[test]
+    fn jobs_are_paginated() {
+        let aliases: HashMap<NodeId, Alias> = HashMap::new();
+        let query = JobsQuery {
+            page: Some(1),
+            per_page: Some(2),
+            ..JobsQuery::default()
+        };
+        let jobs = stub_jobs().jobs(&query, &aliases).unwrap();
+
+        assert_eq!(ids(&jobs), vec!["a1b2c3d4e5f6071829304a5b6c7d8e9f00112233"]);
+    }
+
+    #[test]
+    fn summaries_count_runs_per_commit() {
+        let commit = Oid::from_str("e8c676b9e3b42308dc9d218b70faa5408f8e58ca").unwrap();
+        let summaries = stub_jobs().summaries().unwrap();
+
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(
+            summaries[&commit],
+            Summary {
+                running: 1,
+                failed: 1,
+                succeeded: 1,
+            }
+        );
+
+        let mut value = serde_json::json!({ "id": commit });
+        annotate(&mut value, commit, &summaries);
+        assert_eq!(
+            value["ci"],
+            serde_json::json!({ "running": 1, "failed": 1, "succeeded": 1 })
+        );
+    }
+
+    #[test]
+    fn commits_without_runs_are_not_annotated() {
+        let commit = Oid::from_str("e8c676b9e3b42308dc9d218b70faa5408f8e58ca").unwrap();
+        let summaries = StubJobs(vec![job("e8c676b9e3b42308dc9d218b70faa5408f8e58ca", false)])
+            .summaries()
+            .unwrap();
+        let mut value = serde_json::json!({ "id": commit });
+        annotate(&mut value, commit, &summaries);
+
+        assert!(summaries.is_empty());
+        assert!(value.get("ci").is_none());
+    }
+
     #[test]
     fn jobs_without_runs_are_excluded() {
         let commit = Oid::from_str("e8c676b9e3b42308dc9d218b70faa5408f8e58ca").unwrap();
@@ -0,0 +1,255 @@
+use std::collections::BTreeSet;
+use std::path::Path as FsPath;
+
+use axum::extract::State;
+use axum::response::IntoResponse;
+use axum::Json;
+use serde::Serialize;
+use serde_json::json;
+
+use radicle::cob::patch::cache::Patches as _;
+use radicle::cob::patch::RevisionId;
+use radicle::git::Oid;
+use radicle::storage::ReadRepository;
+
+use crate::api::error::Error;
+use crate::api::Context;
+use crate::axum_extra::Path;
+
+/// The outcome of merging a commit onto a target commit.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum Mergeability {
+    /// The commit merges without conflicts.
+    Clean,
+    /// The merge conflicts on the given paths.
+    Conflicted { conflicts: Vec<String> },
+    /// The merge couldn't be attempted, e.g. because the histories are
+    /// unrelated or the installed git doesn't support `merge-tree --write-tree`.
+    Unknown,
+}
+
+impl Mergeability {
+    /// Merge `head` onto `target` without touching any refs or working copy.
+    ///
+    /// `git merge-tree --write-tree` writes the merged trees and blobs as it
+    /// goes, so it's pointed at a throwaway object directory, with the repo's
+    /// own objects as an alternate: storage is never written to, and the check
+    /// works on read-only storage too.
+    ///
+    /// It exits `0` on a clean merge and `1` when there are conflicts; any
+    /// other outcome is reported as unknown.
+    pub fn check(repo_dir: &FsPath, target: Oid, head: Oid) -> Self {
+        let (target, head) = (target.to_string(), head.to_string());
+        let objects = match tempfile::tempdir() {
+            Ok(objects) => objects,
+            Err(e) => {
+                tracing::debug!("failed to create a scratch object directory: {e}");
+                return Self::Unknown;
+            }
+        };
+        let output = super::git_command(repo_dir)
+            .env("GIT_OBJECT_DIRECTORY", objects.path())
+            .env("GIT_ALTERNATE_OBJECT_DIRECTORIES", repo_dir.join("objects"))
+            .args([
+                "merge-tree",
+                "--write-tree",
+                "--name-only",
+                "--no-messages",
+                "-z",
+                target.as_str(),
+                head.as_str(),
+            ])
+            .output();
+        drop(objects);
+
+        match output {
+            Ok(output) if output.status.code() == Some(0) => Self::Clean,
+            Ok(output) if output.status.code() == Some(1) => Self::Conflicted {
+                conflicts: Self::conflicts(&output.stdout),
+            },
+            Ok(output) => {
+                tracing::debug!(
+                    "git merge-tree of {head} onto {target} failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+                Self::Unknown
+            }
+            Err(e) => {
+                tracing::debug!("failed to run git merge-tree: {e}");
+                Self::Unknown
+            }
+        }
+    }
+
+    /// Parse the conflicted paths out of `git merge-tree -z --name-only`
+    /// output, which is the resulting tree id followed by one NUL-terminated
+    /// path per conflicted stage.
+    fn conflicts(stdout: &[u8]) -> Vec<String> {
+        stdout
+            .split(|b| *b == 0)
+            .skip(1)
+            .take_while(|path| !path.is_empty())
+            .map(|path| String::from_utf8_lossy(path).into_owned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RevisionMergeability {
+    id: RevisionId,
+    head: Oid,
+    #[serde(flatten)]
+    mergeability: Mergeability,
+}
+
+/// Check whether each revision of a patch merges cleanly onto the canonical
+/// head of the repo's default branch.
+/// `GET /repos/:rid/patches/:id/mergeability`
+pub async fn handler(
+    State(ctx): State<Context>,
+    Path((rid, patch_id)): Path<(String, radicle_surf::Oid)>,
+) -> impl IntoResponse {
+    let rid = ctx.resolve_repo(&rid)?;
+    let value = crate::api::blocking(move || {
+        let (repo, _) = ctx.repo(rid)?;
+        let patches = ctx.profile.patches(&repo)?;
+        let patch = patches.get(&patch_id.into())?.ok_or(Error::NotFound)?;
+        let (_, target) = repo.head()?;
+        let revisions = patch
+            .revisions()
+            .map(|(id, revision)| RevisionMergeability {
+                id: *id,
+                head: revision.head(),
+                mergeability: Mergeability::check(repo.backend.path(), target, revision.head()),
+            })
+            .collect::<Vec<_>>();
+
+        Ok::<_, Error>(json!({
+            "target": target,
+            "revisions": revisions,
+        }))
+    })
+    .await?;
+
+    Ok::<_, Error>(Json(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use radicle::git::raw;
+
+    use super::*;
+
+    /// Commit a tree holding a single `README` with `content`.
+    fn commit(repo: &raw::Repository, parent: Option<raw::Oid>, content: &str) -> Oid {
+        let blob = repo.blob(content.as_bytes()).unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("README", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let sig =
+            raw::Signature::new("radicle", "radicle@localhost", &raw::Time::new(0, 0)).unwrap();
+        let parents = parent
+            .map(|oid| repo.find_commit(oid).unwrap())
+            .into_iter()
+            .collect::<Vec<_>>();
+        let parents = parents.iter().collect::<Vec<_>>();
+
+        repo.commit(None, &sig, &sig, content, &tree, &parents)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn merges_are_checked() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = raw::Repository::init_bare(tmp.path()).unwrap();
+        let base = commit(&repo, None, "base\n");
+        let target = commit(&repo, Some(base.into()), "target\n");
+        let head = commit(&repo, Some(base.into()), "head\n");
+        let descendant = commit(&repo, Some(target.into()), "descendant\n");
+
+        assert_eq!(
+            Mergeability::check(tmp.path(), target, head),
+            Mergeability::Conflicted {
+                conflicts: vec!["README".to_owned()]
+            }
+        );
+        assert_eq!(
+            Mergeability::check(tmp.path(), target, descendant),
+            Mergeability::Clean
+        );
+    }
+
+    #[test]
+    fn merges_leave_storage_untouched() {
+        let tmp = tempfile::tempdir().unwrap();
+        let repo = raw::Repository::init_bare(tmp.path()).unwrap();
+        let base = commit(&repo, None, "base\n");
+        let target = commit(&repo, Some(base.into()), "target\n");
+        let head = commit(&repo, Some(base.into()), "head\n");
+        let other = {
+            let parent = repo.find_commit(base.into()).unwrap();
+            let blob = repo.blob(b"other\n").unwrap();
+            let mut builder = repo.treebuilder(Some(&parent.tree().unwrap())).unwrap();
+            builder.insert("OTHER", blob, 0o100644).unwrap();
+            let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+            let sig =
+                raw::Signature::new("radicle", "radicle@localhost", &raw::Time::new(0, 0)).unwrap();
+            Oid::from(
+                repo.commit(None, &sig, &sig, "other", &tree, &[&parent])
+                    .unwrap(),
+            )
+        };
+        let count = || {
+            let mut count = 0;
+            repo.odb()
+                .unwrap()
+                .foreach(|_| {
+                    count += 1;
+                    true
+                })
+                .unwrap();
+            count
+        };
+        let before = count();
+
+        assert!(matches!(
+            Mergeability::check(tmp.path(), target, head),
+            Mergeability::Conflicted { .. }
+        ));
+        assert_eq!(
+            Mergeability::check(tmp.path(), target, other),
+            Mergeability::Clean
+        );
+        assert_eq!(count(), before);
+    }
+
+    #[test]
+    fn conflicts_are_deduplicated() {
+        let stdout = b"1b2d7c1c1ad2fd4f9a7e8c1fe0b7c39b5d6b8a5e\0b.txt\0a.txt\0a.txt\0\0";
+
+        assert_eq!(
+            Mergeability::conflicts(stdout),
+            vec!["a.txt".to_owned(), "b.txt".to_owned()]
+        );
+    }
+
+    #[test]
+    fn mergeability_is_serialized_with_status() {
+        assert_eq!(
+            serde_json::to_value(Mergeability::Conflicted {
+                conflicts: vec!["README".to_owned()]
+            })
+            .unwrap(),
+            json!({ "status": "conflicted", "conflicts": ["README"] })
+        );
+        assert_eq!(
+            serde_json::to_value(Mergeability::Clean).unwrap(),
+            json!({ "status": "clean" })
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::json;
+use uuid::Uuid;
+
+use radicle::crypto::Signature;
+use radicle::identity::Did;
+
+use crate::api::auth::Session;
+use crate::api::error::Error;
+use crate::api::Context;
+use crate::axum_extra::Path;
+
+pub fn router(ctx: Context) -> Router {
+    Router::new()
+        .route("/sessions", post(session_create_handler))
+        .route(
+            "/sessions/{id}",
+            get(session_handler)
+                .put(session_authorize_handler)
+                .delete(session_delete_handler),
+        )
+        .with_state(ctx)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionRequest {
+    did: Did,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AuthorizeRequest {
+    signature: Signature,
+}
+
+fn session_json(id: Uuid, session: &Session) -> serde_json::Value {
+    json!({
+        "sessionId": id,
+        "status": session.status,
+        "did": session.did,
+        "challenge": session.challenge,
+        "issuedAt": session.issued_at,
+        "expiresAt": session.expires_at,
+    })
+}
+
+/// Open a session for a DID. The returned challenge must be signed with the
+/// DID's key, e.g. through `ssh-agent`, to authorize the session.
+/// `POST /sessions`
+async fn session_create_handler(
+    State(ctx): State<Context>,
+    Json(request): Json<SessionRequest>,
+) -> impl IntoResponse {
+    // Changes are signed with the node's key, so only its own identity may
+    // act through the write API.
+    if request.did != ctx.profile.did() {
+        return Err(Error::Forbidden(
+            "sessions can only be opened for the node's identity",
+        ));
+    }
+    let (id, session) = ctx.sessions.create(request.did).await;
+
+    Ok::<_, Error>((StatusCode::CREATED, Json(session_json(id, &session))))
+}
+
+/// Get a session.
+/// `GET /sessions/:id`
+async fn session_handler(State(ctx): State<Context>, Path(id): Path<Uuid>) -> impl IntoResponse {
+    let session = ctx.sessions.get(&id).await.ok_or(Error::NotFound)?;
+
+    Ok::<_, Error>(Json(session_json(id, &session)))
+}
+
+/// Authorize a session with a signature over its challenge.
+/// `PUT /sessions/:id`
+async fn session_authorize_handler(
+    State(ctx): State<Context>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<AuthorizeRequest>,
+) -> impl IntoResponse {
+    let session = ctx.sessions.authorize(&id, &request.signature).await?;
+
+    Ok::<_, Error>(Json(session_json(id, &session)))
+}
+
+/// Close a session.
+/// `DELETE /sessions/:id`
+async fn session_delete_handler(
+    State(ctx): State<Context>,
+    Path(id): Path<Uuid>,
+) -> impl IntoResponse {
+    if ctx.sessions.remove(&id).await {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod routes {
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use radicle::crypto::{Seed, Signer, SigningKey};
+    use serde_json::json;
+
+    use crate::test::{self, delete, get, post, put, DID};
+
+    #[tokio::test]
+    async fn test_session_lifecycle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let signer = SigningKey::from_seed(Seed::new([0xff; 32]));
+
+        let response = post(
+            &app,
+            "/sessions",
+            Some(Body::from(json!({ "did": DID }).to_string())),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let session = response.json().await;
+        assert_eq!(session["status"], "unauthorized");
+        let id = session["sessionId"].as_str().unwrap().to_owned();
+        let challenge = session["challenge"].as_str().unwrap().to_owned();
+
+        let signature = signer.sign(challenge.as_bytes());
+        let response = put(
+            &app,
+            format!("/sessions/{id}"),
+            Some(Body::from(json!({ "signature": signature }).to_string())),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.json().await["status"], "authorized");
+
+        let response = get(&app, format!("/sessions/{id}")).await;
+        assert_eq!(response.json().await["did"], DID);
+
+        let response = delete(&app, format!("/sessions/{id}"), None).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = get(&app, format!("/sessions/{id}")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_session_foreign_did() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let other = SigningKey::from_seed(Seed::new([0xee; 32]));
+        let did = radicle::identity::Did::from(*other.public_key());
+
+        let response = post(
+            &app,
+            "/sessions",
+            Some(Body::from(json!({ "did": did }).to_string())),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
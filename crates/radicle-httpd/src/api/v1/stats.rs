@@ -18,15 +18,22 @@ pub fn router(ctx: Context) -> Router {
 /// Return the stats for the node.
 /// `GET /stats`
 async fn stats_handler(State(ctx): State<Context>) -> impl IntoResponse {
-    let total_seeded = crate::api::blocking(move || {
-        let db = ctx.profile.database()?;
-        let nid = ctx.profile.public_key;
+    let total_seeded = crate::api::blocking({
+        let ctx = ctx.clone();
+        move || {
+            let db = ctx.profile.database()?;
+            let nid = ctx.profile.public_key;
 
-        Ok::<_, Error>(db.get_inventory(&nid)?.len())
+            Ok::<_, Error>(db.get_inventory(&nid)?.len())
+        }
     })
     .await?;
+    let mut stats = json!({ "repos": { "total": total_seeded } });
 
-    Ok::<_, Error>(Json(json!({ "repos": { "total": total_seeded } })))
+    if let Some(cache) = ctx.cache_stats().await {
+        stats["cache"] = json!(cache);
+    }
+    Ok::<_, Error>(Json(stats))
 }
 
 #[cfg(test)]
@@ -43,6 +50,17 @@ mod routes {
         let response = get(&app, "/stats").await;
 
         assert_eq!(response.status(), StatusCode::OK);
-        assert_eq!(response.json().await, json!({ "repos": { "total": 2 } }));
+        assert_eq!(
+            response.json().await,
+            json!({
+                "repos": { "total": 2 },
+                "cache": {
+                    "size": 0,
+                    "capacity": 100 * 1024 * 1024,
+                    "entries": 0,
+                    "endpoints": {},
+                },
+            })
+        );
     }
 }
@@ -0,0 +1,40 @@
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+
+use crate::api::error::Error;
+use crate::api::Context;
+
+/// Routes to inspect outgoing webhooks. Only mounted when httpd runs with
+/// `--write`, since they require an authorized session.
+pub fn router(ctx: Context) -> Router {
+    Router::new()
+        .route("/webhooks/deliveries", get(deliveries_handler))
+        .with_state(ctx)
+}
+
+/// The log of recent webhook deliveries, newest first.
+/// `GET /webhooks/deliveries`
+async fn deliveries_handler(State(ctx): State<Context>, headers: HeaderMap) -> impl IntoResponse {
+    ctx.sessions.authenticate(&headers).await?;
+
+    Ok::<_, Error>(Json(ctx.webhooks().deliveries()))
+}
+
+#[cfg(test)]
+mod routes {
+    use axum::http::StatusCode;
+
+    use crate::test::{self, get};
+
+    #[tokio::test]
+    async fn test_deliveries_require_session() {
+        let tmp = tempfile::tempdir().unwrap();
+        let app = super::router(test::seed(tmp.path()));
+        let response = get(&app, "/webhooks/deliveries").await;
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
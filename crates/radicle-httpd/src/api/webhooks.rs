@@ -0,0 +1,723 @@
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use url::Url;
+use uuid::Uuid;
+
+use radicle::cob::{issue::cache::Issues as _, patch::cache::Patches as _, ObjectId};
+use radicle::crypto::{Signature, Signer as _};
+use radicle::git::Oid;
+use radicle::identity::{Did, RepoId};
+use radicle::node::NodeId;
+use radicle::storage::ReadStorage as _;
+use radicle::Profile;
+use radicle_job::JobId;
+
+use crate::api::error::Error;
+use crate::api::events::Envelope;
+use crate::api::{json, Context};
+
+/// Number of deliveries kept in the delivery log.
+pub const LOG_SIZE: usize = 256;
+/// Number of times a delivery is attempted before giving up.
+pub const MAX_ATTEMPTS: usize = 5;
+/// Delay before the first retry. Doubles with every further attempt.
+pub const BACKOFF: Duration = Duration::from_secs(2);
+/// Timeout of a single delivery attempt.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The activity a webhook can subscribe to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Kind {
+    /// A canonical ref, e.g. the default branch, moved.
+    CanonicalRefUpdated,
+    /// A new issue was fetched, or opened on this node.
+    IssueOpened,
+    /// A new patch was fetched, or opened on this node.
+    PatchOpened,
+    /// A node added or updated its runs of a CI job.
+    JobRun,
+}
+
+/// A webhook, configured under `web.webhooks` in the profile's
+/// `config.json`, e.g.
+///
+/// ```json
+/// { "url": "https://ci.example.com/hook", "repos": ["rad:z4Fuc…"], "events": ["canonicalRefUpdated"] }
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hook {
+    pub url: Url,
+    /// Repos to deliver events for. All public repos when empty.
+    #[serde(default)]
+    pub repos: Vec<RepoId>,
+    /// Events to deliver. All of them when empty.
+    #[serde(default)]
+    pub events: Vec<Kind>,
+}
+
+impl Hook {
+    fn matches(&self, rid: &RepoId, kind: Kind) -> bool {
+        (self.repos.is_empty() || self.repos.contains(rid))
+            && (self.events.is_empty() || self.events.contains(&kind))
+    }
+}
+
+/// Read the webhooks from the `web.webhooks` key of the profile's config.
+/// The key isn't part of [`radicle::web::Config`], so the file is read as
+/// plain JSON. A missing or invalid key disables webhooks.
+pub fn load(profile: &Profile) -> Vec<Hook> {
+    let path = profile.home().config();
+    let config = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to read {}: {e}", path.display());
+            return Vec::new();
+        }
+    };
+    let hooks = serde_json::from_slice::<Value>(&config).and_then(|config| {
+        match config.get("web").and_then(|web| web.get("webhooks")) {
+            Some(hooks) => serde_json::from_value(hooks.clone()),
+            None => Ok(Vec::new()),
+        }
+    });
+
+    match hooks {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            tracing::error!("Invalid webhook configuration, webhooks are disabled: {e}");
+            Vec::new()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeliveryState {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attempt {
+    pub timestamp: i64,
+    /// The response status, if a response was received.
+    pub status: Option<u16>,
+    pub error: Option<String>,
+}
+
+/// An entry of the delivery log.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub id: Uuid,
+    pub url: Url,
+    pub event: Kind,
+    pub rid: RepoId,
+    pub state: DeliveryState,
+    pub attempts: Vec<Attempt>,
+}
+
+/// A COB ref that was created or updated, by a fetch or locally.
+#[derive(Debug, PartialEq, Eq)]
+struct CobUpdate {
+    /// The namespace the ref lives in.
+    remote: NodeId,
+    typename: String,
+    id: ObjectId,
+    created: bool,
+}
+
+/// Extract the COB updates from a `refsFetched` event.
+fn cob_updates(data: &Value) -> Vec<CobUpdate> {
+    let Some(updated) = data.get("updated").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+
+    updated
+        .iter()
+        .filter_map(|update| {
+            let created = match update.get("type").and_then(Value::as_str)? {
+                "created" => true,
+                "updated" => false,
+                _ => return None,
+            };
+            let name = update.get("name").and_then(Value::as_str)?;
+
+            cob_update(name, created)
+        })
+        .collect()
+}
+
+/// Parse a namespaced COB ref, e.g.
+/// `refs/namespaces/<nid>/refs/cobs/xyz.radicle.issue/<id>`.
+fn cob_update(name: &str, created: bool) -> Option<CobUpdate> {
+    let (remote, cob) = name
+        .strip_prefix("refs/namespaces/")?
+        .split_once("/refs/cobs/")?;
+    let (typename, id) = cob.split_once('/')?;
+
+    Some(CobUpdate {
+        remote: NodeId::from_str(remote).ok()?,
+        typename: typename.to_owned(),
+        id: ObjectId::from_str(id).ok()?,
+        created,
+    })
+}
+
+/// The node's own COB refs of each repo, as last seen.
+///
+/// Announcing local refs doesn't say which refs changed, so a
+/// `localRefsAnnounced` event is turned into COB updates by comparing the
+/// node's COB refs with the ones seen at the previous announcement.
+#[derive(Clone, Default)]
+struct LocalCobs(Arc<Mutex<HashMap<RepoId, HashMap<String, Oid>>>>);
+
+impl LocalCobs {
+    /// Record the current COB refs of every public repo.
+    fn snapshot(&self, profile: &Profile) -> Result<(), Error> {
+        let mut seen = HashMap::new();
+        for info in profile.storage.repositories()? {
+            if !info.doc.visibility().is_public() {
+                continue;
+            }
+            let repo = profile.storage.repository(info.rid)?;
+            seen.insert(info.rid, Self::refs(&repo, &profile.public_key)?);
+        }
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = seen;
+
+        Ok(())
+    }
+
+    /// The COB refs created or moved since they were last seen. Every ref of
+    /// a repo that wasn't seen before counts as created.
+    fn updates(
+        &self,
+        repo: &radicle::storage::git::Repository,
+        nid: &NodeId,
+    ) -> Result<Vec<CobUpdate>, Error> {
+        let refs = Self::refs(repo, nid)?;
+        let mut seen = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let last = seen.remove(&repo.id).unwrap_or_default();
+        let updates = refs
+            .iter()
+            .filter_map(|(name, oid)| match last.get(name) {
+                None => cob_update(name, true),
+                Some(last) if last != oid => cob_update(name, false),
+                Some(_) => None,
+            })
+            .collect();
+        seen.insert(repo.id, refs);
+
+        Ok(updates)
+    }
+
+    /// The COB refs in `nid`'s namespace.
+    fn refs(
+        repo: &radicle::storage::git::Repository,
+        nid: &NodeId,
+    ) -> Result<HashMap<String, Oid>, Error> {
+        let mut refs = HashMap::new();
+        for r in repo
+            .backend
+            .references_glob(&format!("refs/namespaces/{nid}/refs/cobs/*"))?
+        {
+            let r = r?;
+            if let (Some(name), Some(oid)) = (r.name(), r.target()) {
+                refs.insert(name.to_owned(), oid.into());
+            }
+        }
+        Ok(refs)
+    }
+}
+
+/// Posts signed JSON payloads to the configured webhooks as node events
+/// come in.
+///
+/// Payloads are signed with the node's key: the `X-Radicle-Signature`
+/// header holds the signature over the request body, which receivers can
+/// verify against the NID in `X-Radicle-Node`.
+#[derive(Clone)]
+pub struct Webhooks {
+    hooks: Arc<RwLock<Vec<Hook>>>,
+    log: Arc<Mutex<VecDeque<Delivery>>>,
+    local: LocalCobs,
+    client: reqwest::Client,
+    backoff: Duration,
+}
+
+impl Webhooks {
+    pub fn new(hooks: Vec<Hook>) -> Self {
+        Self {
+            hooks: Arc::new(RwLock::new(hooks)),
+            log: Arc::default(),
+            local: LocalCobs::default(),
+            client: reqwest::Client::builder()
+                .timeout(TIMEOUT)
+                .user_agent(concat!("radicle-httpd/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .expect("Webhooks::new: TLS backend must be available"),
+            backoff: BACKOFF,
+        }
+    }
+
+    /// Replace the configured webhooks, e.g. after a configuration reload.
+    pub async fn set_hooks(&self, hooks: Vec<Hook>) {
+        *self.hooks.write().await = hooks;
+    }
+
+    #[cfg(test)]
+    pub fn set_backoff(&mut self, backoff: Duration) {
+        self.backoff = backoff;
+    }
+
+    /// The delivery log, newest first.
+    pub fn deliveries(&self) -> Vec<Delivery> {
+        let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        log.iter().rev().cloned().collect()
+    }
+
+    /// Deliver webhooks for the events relayed by [`Context::events`] until
+    /// the process exits.
+    pub async fn run(self, ctx: Context) {
+        let (_, mut rx) = ctx.events().subscribe(None);
+        let snapshot = {
+            let (local, profile) = (self.local.clone(), ctx.profile.clone());
+            crate::api::blocking(move || local.snapshot(&profile)).await
+        };
+        if let Err(e) = snapshot {
+            tracing::warn!("Failed to read local COBs for webhooks: {e}");
+        }
+
+        loop {
+            match rx.recv().await {
+                Ok(envelope) => self.dispatch(&ctx, envelope).await,
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("Webhooks fell behind, {n} event(s) were not delivered");
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+
+    /// Deliver an event to every matching webhook.
+    async fn dispatch(&self, ctx: &Context, envelope: Arc<Envelope>) {
+        let hooks = self.hooks.read().await.clone();
+        if hooks.is_empty() {
+            return;
+        }
+        let rid = envelope.rid;
+        let triggers = {
+            let (ctx, local) = (ctx.clone(), self.local.clone());
+            crate::api::blocking(move || triggers(&ctx, &envelope, &local)).await
+        };
+        let triggers = match triggers {
+            Ok(triggers) => triggers,
+            Err(e) => {
+                tracing::warn!("Failed to process event of {rid} for webhooks: {e}");
+                return;
+            }
+        };
+
+        for (kind, data) in triggers {
+            for hook in hooks.iter().filter(|hook| hook.matches(&rid, kind)) {
+                let id = Uuid::new_v4();
+                let payload = json!({
+                    "id": id,
+                    "type": kind,
+                    "rid": rid,
+                    "node": ctx.profile.public_key,
+                    "timestamp": chrono::Utc::now().timestamp(),
+                    "data": data,
+                });
+                let body = payload.to_string();
+                let signature: Signature = match ctx.profile.signer() {
+                    Ok(signer) => signer.sign(body.as_bytes()),
+                    Err(e) => {
+                        tracing::error!("Unable to sign webhook payloads: {e}");
+                        return;
+                    }
+                };
+                let delivery = Delivery {
+                    id,
+                    url: hook.url.clone(),
+                    event: kind,
+                    rid,
+                    state: DeliveryState::Pending,
+                    attempts: Vec::new(),
+                };
+                let node = ctx.profile.public_key;
+
+                tokio::spawn(self.clone().deliver(delivery, node, body, signature));
+            }
+        }
+    }
+
+    /// Post a payload, retrying with exponential backoff until the receiver
+    /// accepts it, or the attempts are exhausted.
+    async fn deliver(self, delivery: Delivery, node: NodeId, body: String, signature: Signature) {
+        let id = delivery.id;
+        let url = delivery.url.clone();
+        let event = serde_json::to_value(delivery.event).unwrap_or_default();
+        self.record(delivery);
+
+        let mut backoff = self.backoff;
+        for attempt in 1..=MAX_ATTEMPTS {
+            let response = self
+                .client
+                .post(url.clone())
+                .header("Content-Type", "application/json")
+                .header("X-Radicle-Delivery", id.to_string())
+                .header("X-Radicle-Event", event.as_str().unwrap_or_default())
+                .header("X-Radicle-Node", node.to_string())
+                .header("X-Radicle-Signature", signature.to_string())
+                .body(body.clone())
+                .send()
+                .await;
+            let (status, error, retry) = match response {
+                Ok(response) => {
+                    let status = response.status();
+                    // Other client errors won't go away by retrying.
+                    let retry = status.is_server_error()
+                        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+                        || status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+                    (Some(status.as_u16()), None, retry)
+                }
+                Err(e) => (None, Some(e.to_string()), true),
+            };
+            let delivered = status.is_some_and(|s| (200..300).contains(&s));
+            let state = if delivered {
+                DeliveryState::Delivered
+            } else if retry && attempt < MAX_ATTEMPTS {
+                DeliveryState::Pending
+            } else {
+                DeliveryState::Failed
+            };
+
+            self.update(
+                &id,
+                state,
+                Attempt {
+                    timestamp: chrono::Utc::now().timestamp(),
+                    status,
+                    error,
+                },
+            );
+            match state {
+                DeliveryState::Pending => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+                DeliveryState::Delivered => return,
+                DeliveryState::Failed => {
+                    tracing::warn!(
+                        "Webhook delivery {id} to {url} failed after {attempt} attempt(s)"
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    fn record(&self, delivery: Delivery) {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        if log.len() == LOG_SIZE {
+            log.pop_front();
+        }
+        log.push_back(delivery);
+    }
+
+    fn update(&self, id: &Uuid, state: DeliveryState, attempt: Attempt) {
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(delivery) = log.iter_mut().find(|d| d.id == *id) {
+            delivery.state = state;
+            delivery.attempts.push(attempt);
+        }
+    }
+}
+
+/// Work out which webhook events a node event triggers, along with their
+/// data.
+#[allow(clippy::result_large_err)]
+fn triggers(
+    ctx: &Context,
+    envelope: &Envelope,
+    local: &LocalCobs,
+) -> Result<Vec<(Kind, Value)>, Error> {
+    let mut triggers = Vec::new();
+
+    let (repo, updates) = match envelope.kind.as_str() {
+        "canonicalRefUpdated" => {
+            let mut data = envelope.data.clone();
+            if let Some(data) = data.as_object_mut() {
+                data.remove("type");
+                data.remove("rid");
+            }
+            triggers.push((Kind::CanonicalRefUpdated, data));
+
+            return Ok(triggers);
+        }
+        "refsFetched" => {
+            let updates = cob_updates(&envelope.data);
+            if updates.is_empty() {
+                return Ok(triggers);
+            }
+            let (repo, _) = ctx.repo(envelope.rid)?;
+
+            (repo, updates)
+        }
+        "localRefsAnnounced" => {
+            let (repo, _) = ctx.repo(envelope.rid)?;
+            let updates = local.updates(&repo, &ctx.profile.public_key)?;
+
+            (repo, updates)
+        }
+        _ => return Ok(triggers),
+    };
+    let aliases = ctx.profile.aliases();
+
+    for update in updates {
+        match update.typename.as_str() {
+            // Anyone taking part in an issue or patch gets a ref to it, so
+            // only the author's ref being created means it's new.
+            "xyz.radicle.issue" if update.created => {
+                let Some(issue) = ctx.profile.issues(&repo)?.get(&update.id)? else {
+                    continue;
+                };
+                if *issue.author().id() != Did::from(update.remote) {
+                    continue;
+                }
+                let mut data = json::cobs::Issue::new(&issue).as_json(update.id, &aliases);
+                json::link_embeds(&mut data, &envelope.rid, &update.id);
+                triggers.push((Kind::IssueOpened, data));
+            }
+            "xyz.radicle.patch" if update.created => {
+                let Some(patch) = ctx.profile.patches(&repo)?.get(&update.id)? else {
+                    continue;
+                };
+                if *patch.author().id() != Did::from(update.remote) {
+                    continue;
+                }
+                let mut data = json::cobs::Patch::new(&patch).as_json(update.id, &repo, &aliases);
+                json::link_embeds(&mut data, &envelope.rid, &update.id);
+                triggers.push((Kind::PatchOpened, data));
+            }
+            // Each node keeps its runs of a job under its own ref.
+            "xyz.radicle.job" => {
+                let store = radicle_job::Jobs::open(&repo, radicle::cob::store::access::ReadOnly)?;
+                let Some(job) = store.get(&JobId::from(update.id))? else {
+                    continue;
+                };
+                triggers.push((
+                    Kind::JobRun,
+                    json!({
+                        "jobId": update.id,
+                        "commit": job.oid(),
+                        "node": json::Author::new(&Did::from(update.remote)).as_json(&aliases),
+                    }),
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(triggers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::routing::post;
+    use axum::Router;
+    use radicle::cob::Title;
+    use radicle::crypto::{PublicKey, Seed, SigningKey};
+
+    use super::*;
+    use crate::test::{self, ISSUE_ID, RID};
+
+    #[test]
+    fn hooks_match_repos_and_events() {
+        let rid = RepoId::from_str(RID).unwrap();
+        let hook = Hook {
+            url: Url::parse("http://localhost/hook").unwrap(),
+            repos: Vec::new(),
+            events: Vec::new(),
+        };
+        assert!(hook.matches(&rid, Kind::JobRun));
+
+        let hook = Hook {
+            repos: vec![rid],
+            events: vec![Kind::CanonicalRefUpdated],
+            ..hook
+        };
+        assert!(hook.matches(&rid, Kind::CanonicalRefUpdated));
+        assert!(!hook.matches(&rid, Kind::IssueOpened));
+        assert!(!hook.matches(
+            &test::RID_PRIVATE.parse().unwrap(),
+            Kind::CanonicalRefUpdated
+        ));
+    }
+
+    #[test]
+    fn cob_updates_are_parsed() {
+        let nid = "z6MknSLrJoTcukLrE435hVNQT4JUhbvWLX4kUzqkEStBU8Vi";
+        let data = json!({
+            "type": "refsFetched",
+            "updated": [
+                { "type": "created", "name": format!("refs/namespaces/{nid}/refs/cobs/xyz.radicle.issue/{ISSUE_ID}"), "oid": ISSUE_ID },
+                { "type": "updated", "name": format!("refs/namespaces/{nid}/refs/heads/master"), "old": ISSUE_ID, "new": ISSUE_ID },
+                { "type": "skipped", "name": format!("refs/namespaces/{nid}/refs/cobs/xyz.radicle.patch/{ISSUE_ID}"), "oid": ISSUE_ID },
+            ],
+        });
+
+        assert_eq!(
+            cob_updates(&data),
+            vec![CobUpdate {
+                remote: NodeId::from_str(nid).unwrap(),
+                typename: "xyz.radicle.issue".to_owned(),
+                id: ObjectId::from_str(ISSUE_ID).unwrap(),
+                created: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn local_issues_are_triggered() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let rid = RepoId::from_str(RID).unwrap();
+        let local = LocalCobs::default();
+        local.snapshot(&ctx.profile).unwrap();
+        let announced = || {
+            ctx.events()
+                .publish(rid, json!({ "type": "localRefsAnnounced", "rid": rid }))
+        };
+
+        assert_eq!(triggers(&ctx, &announced(), &local).unwrap(), vec![]);
+
+        let repo = ctx.profile.storage.repository(rid).unwrap();
+        let signer = SigningKey::from_seed(Seed::new([0xff; 32]));
+        let mut issues = ctx.profile.issues_mut(&repo, &signer).unwrap();
+        let issue = issues
+            .create(
+                Title::new("Opened on the seed").unwrap(),
+                "Nothing was fetched".to_string(),
+                &[],
+                &[],
+                [],
+            )
+            .unwrap();
+        let id = issue.id().to_string();
+
+        let opened = triggers(&ctx, &announced(), &local).unwrap();
+        assert_eq!(opened.len(), 1);
+        assert_eq!(opened[0].0, Kind::IssueOpened);
+        assert_eq!(opened[0].1["id"], id);
+
+        assert_eq!(triggers(&ctx, &announced(), &local).unwrap(), vec![]);
+    }
+
+    /// Serve a local stand-in receiver that fails its first request, and
+    /// records every request it gets.
+    async fn receiver() -> (SocketAddr, Arc<Mutex<Vec<(HeaderMap, String)>>>) {
+        let requests = Arc::<Mutex<Vec<(HeaderMap, String)>>>::default();
+        let app = Router::new().route(
+            "/hook",
+            post({
+                let requests = requests.clone();
+                move |headers: HeaderMap, body: String| {
+                    let requests = requests.clone();
+                    async move {
+                        let mut requests = requests.lock().unwrap();
+                        requests.push((headers, body));
+                        if requests.len() == 1 {
+                            StatusCode::SERVICE_UNAVAILABLE
+                        } else {
+                            StatusCode::NO_CONTENT
+                        }
+                    }
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (addr, requests)
+    }
+
+    #[tokio::test]
+    async fn test_webhook_delivery() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let rid = RepoId::from_str(RID).unwrap();
+        let (addr, requests) = receiver().await;
+        let mut webhooks = Webhooks::new(vec![Hook {
+            url: Url::parse(&format!("http://{addr}/hook")).unwrap(),
+            repos: vec![rid],
+            events: vec![Kind::CanonicalRefUpdated],
+        }]);
+        webhooks.set_backoff(Duration::from_millis(10));
+
+        let refs = ctx.events().publish(
+            rid,
+            json!({ "type": "canonicalRefUpdated", "rid": rid, "refname": "refs/heads/master", "target": test::HEAD }),
+        );
+        let unrelated = ctx
+            .events()
+            .publish(rid, json!({ "type": "seedDiscovered", "rid": rid }));
+        webhooks.dispatch(&ctx, unrelated).await;
+        webhooks.dispatch(&ctx, refs).await;
+
+        let delivery = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                match webhooks.deliveries().as_slice() {
+                    [delivery] if delivery.state != DeliveryState::Pending => {
+                        return delivery.clone();
+                    }
+                    _ => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(delivery.state, DeliveryState::Delivered);
+        assert_eq!(
+            delivery
+                .attempts
+                .iter()
+                .map(|a| a.status)
+                .collect::<Vec<_>>(),
+            vec![Some(503), Some(204)]
+        );
+
+        let requests = requests.lock().unwrap();
+        let (headers, body) = requests.last().unwrap();
+        let payload: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(payload["id"], delivery.id.to_string());
+        assert_eq!(payload["type"], "canonicalRefUpdated");
+        assert_eq!(payload["rid"], RID);
+        assert_eq!(
+            payload["data"],
+            json!({ "refname": "refs/heads/master", "target": test::HEAD })
+        );
+        assert_eq!(headers["X-Radicle-Event"], "canonicalRefUpdated");
+
+        let node = PublicKey::from_str(headers["X-Radicle-Node"].to_str().unwrap()).unwrap();
+        let signature =
+            Signature::from_str(headers["X-Radicle-Signature"].to_str().unwrap()).unwrap();
+        assert_eq!(node, ctx.profile().public_key);
+        assert!(node.verify(body.as_bytes(), &signature).is_ok());
+    }
+}
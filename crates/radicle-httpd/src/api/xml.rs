@@ -0,0 +1,39 @@
+/// Escape text for use in XML attribute values and text nodes. Characters
+/// XML 1.0 doesn't allow at all, like most control characters, are replaced
+/// with U+FFFD, as a single one would make the whole document unparsable.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {
+                escaped.push(char::REPLACEMENT_CHARACTER)
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+        assert_eq!(escape("line\r\n\tend"), "line\r\n\tend");
+        assert_eq!(
+            escape("bell\u{7}, nul\u{0}, esc\u{1b}"),
+            "bell\u{fffd}, nul\u{fffd}, esc\u{fffd}"
+        );
+    }
+}
@@ -1,9 +1,10 @@
+use axum::body::Bytes;
 use axum::extract::path::ErrorKind;
 use axum::extract::rejection::{PathRejection, QueryRejection};
 use axum::extract::FromRequestParts;
 use axum::http::request::Parts;
-use axum::http::{header, StatusCode};
-use axum::response::IntoResponse;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
 
 use serde::de::DeserializeOwned;
@@ -96,6 +97,18 @@ pub fn immutable_response(data: impl serde::Serialize) -> impl IntoResponse {
     )
 }
 
+/// Like [`immutable_response`], for a body that is already serialized JSON.
+pub fn immutable_json(body: Bytes) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "application/json"),
+            (header::CACHE_CONTROL, "public, max-age=604800, immutable"),
+        ],
+        body,
+    )
+        .into_response()
+}
+
 /// Add a Cache-Control header that marks the response as must-revalidate and
 /// instructs clients to cache the response for `max_age_seconds` .
 pub fn cached_response(data: impl serde::Serialize, max_age_in_seconds: u64) -> impl IntoResponse {
@@ -107,3 +120,63 @@ pub fn cached_response(data: impl serde::Serialize, max_age_in_seconds: u64) ->
         Json(data),
     )
 }
+
+/// A strong entity tag, see <https://www.rfc-editor.org/rfc/rfc9110#name-etag>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag(String);
+
+impl ETag {
+    /// Create an entity tag from an opaque value, which must not contain
+    /// double quotes.
+    pub fn new(value: impl std::fmt::Display) -> Self {
+        Self(format!("\"{value}\""))
+    }
+
+    /// The tag as sent in the `ETag` header, including the quotes.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether the request's `If-None-Match` header lists this tag, ie. the
+    /// client's copy is still current. As required for `If-None-Match`, weak
+    /// tags sent back by clients compare equal to their strong counterpart.
+    pub fn matches(&self, headers: &HeaderMap) -> bool {
+        headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == self.0)
+    }
+
+    /// An empty `304 Not Modified` response, for when [`ETag::matches`].
+    pub fn not_modified(&self) -> Response {
+        (StatusCode::NOT_MODIFIED, self.headers()).into_response()
+    }
+
+    /// Respond with `data`, tagged with this entity tag.
+    pub fn response(&self, data: impl serde::Serialize) -> Response {
+        (self.headers(), Json(data)).into_response()
+    }
+
+    /// Respond with a body that is already serialized JSON, tagged with
+    /// this entity tag.
+    pub fn json(&self, body: Bytes) -> Response {
+        (
+            self.headers(),
+            [(header::CONTENT_TYPE, "application/json")],
+            body,
+        )
+            .into_response()
+    }
+
+    /// Clients and caches must revalidate on every use, which is cheap as
+    /// long as the tag didn't change.
+    fn headers(&self) -> [(header::HeaderName, String); 2] {
+        [
+            (header::ETAG, self.0.clone()),
+            (header::CACHE_CONTROL, "public, no-cache".to_owned()),
+        ]
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use radicle::git;
+use radicle::git::fmt::RefString;
+use radicle::identity::{Doc, RepoId};
+use radicle::storage::git::{paths, Repository};
+use radicle::storage::{ReadRepository as _, ReadStorage as _};
+use radicle::Profile;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::api::events::Events;
+use crate::cache::INVALIDATING_EVENTS;
+
+/// How often every bundle is checked against its repo's canonical refs, to
+/// pick up changes the node doesn't report.
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Where a repo's bundle is served, under its git URL.
+pub const BUNDLE_PATH: &str = "clone.bundle";
+
+/// A prebuilt bundle of a repo's canonical refs.
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    pub path: PathBuf,
+    /// Hash of the canonical refs the bundle was built from.
+    pub fingerprint: String,
+}
+
+/// Bundles of every public repo, kept in a directory and rebuilt whenever
+/// their canonical refs change. Git clients that support `bundle-uri` fetch
+/// them as static files before asking `git http-backend` for the rest.
+///
+/// Each repo has a directory of its own, holding a single bundle named after
+/// its fingerprint.
+#[derive(Clone)]
+pub struct Bundles {
+    dir: PathBuf,
+    table: Arc<RwLock<HashMap<RepoId, Bundle>>>,
+}
+
+impl Bundles {
+    /// Open the bundle directory at `dir`, creating it if needed, and pick up
+    /// the bundles built by earlier runs.
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        let mut table = HashMap::new();
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let Some(rid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| format!("rad:{name}").parse::<RepoId>().ok())
+            else {
+                continue;
+            };
+            for file in fs::read_dir(entry.path())? {
+                let path = file?.path();
+                let fingerprint = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_suffix(".bundle"));
+
+                match fingerprint {
+                    Some(fingerprint) if !table.contains_key(&rid) => {
+                        let fingerprint = fingerprint.to_owned();
+                        table.insert(rid, Bundle { path, fingerprint });
+                    }
+                    // Leftovers of interrupted builds or of crashes between
+                    // replacing a bundle and removing the old one.
+                    _ => fs::remove_file(&path)?,
+                }
+            }
+        }
+
+        Ok(Self {
+            dir: dir.to_owned(),
+            table: Arc::new(RwLock::new(table)),
+        })
+    }
+
+    /// The bundle of a public repo, if it's built.
+    pub fn get(&self, rid: &RepoId) -> Option<Bundle> {
+        self.table
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(rid)
+            .cloned()
+    }
+
+    /// Rebuild the bundle of `rid` if its canonical refs changed, dropping
+    /// it if the repo is gone, not public anymore or has no canonical refs.
+    ///
+    /// Runs `git bundle create`, so this is blocking and can take a while.
+    pub fn refresh(&self, profile: &Profile, rid: RepoId) -> io::Result<()> {
+        let repo = profile.storage.repository(rid).ok();
+        let doc = repo
+            .as_ref()
+            .and_then(|repo| repo.identity_doc().ok())
+            .filter(|doc| doc.visibility().is_public());
+        let refs = match (repo, doc) {
+            (Some(repo), Some(doc)) => canonical_refs(&repo, &doc.doc)?,
+            _ => Vec::new(),
+        };
+        let fingerprint = fingerprint(&refs).map_err(io::Error::other)?;
+        let Some(fingerprint) = fingerprint else {
+            return self.remove(&rid);
+        };
+        if self
+            .get(&rid)
+            .is_some_and(|bundle| bundle.fingerprint == fingerprint)
+        {
+            return Ok(());
+        }
+        let dir = self.dir.join(dir_name(&rid));
+        let staged = dir.join(format!("{fingerprint}.tmp"));
+        let path = dir.join(format!("{fingerprint}.bundle"));
+        let git_dir = paths::repository(&profile.storage, &rid);
+
+        fs::create_dir_all(&dir)?;
+        let output = Command::new("git")
+            .arg("--git-dir")
+            .arg(git_dir)
+            .args(["bundle", "create", "--quiet"])
+            .arg(&staged)
+            .args(refs.iter().map(|(refname, _)| refname.as_str()))
+            .output()?;
+        if !output.status.success() {
+            fs::remove_file(&staged).ok();
+            return Err(io::Error::other(format!(
+                "git bundle create failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        fs::rename(&staged, &path)?;
+        tracing::debug!("Built bundle {} for {rid}", path.display());
+
+        let bundle = Bundle { path, fingerprint };
+        let old = self
+            .table
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(rid, bundle);
+        // Clients still downloading the old bundle keep their open file.
+        if let Some(old) = old {
+            fs::remove_file(old.path)?;
+        }
+        Ok(())
+    }
+
+    /// Rebuild every bundle whose canonical refs changed.
+    pub fn refresh_all(&self, profile: &Profile) -> io::Result<()> {
+        let repos = profile.storage.repositories().map_err(io::Error::other)?;
+        let rids = repos.iter().map(|info| info.rid).collect::<Vec<_>>();
+        let gone = self
+            .table
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .keys()
+            .filter(|rid| !rids.contains(rid))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for rid in gone.into_iter().chain(rids) {
+            if let Err(e) = self.refresh(profile, rid) {
+                tracing::warn!("Failed to refresh bundle of {rid}: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep the bundles up to date until the process exits.
+    pub async fn run(self, profile: Arc<Profile>, events: Events) {
+        let (_, mut rx) = events.subscribe(None);
+        let mut withheld = events.withheld();
+        let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+
+        loop {
+            let rid = tokio::select! {
+                _ = interval.tick() => None,
+                result = rx.recv() => match result {
+                    Ok(envelope) if INVALIDATING_EVENTS.contains(&envelope.kind.as_str()) => {
+                        Some(envelope.rid)
+                    }
+                    Ok(_) => continue,
+                    // Some events were missed.
+                    Err(RecvError::Lagged(_)) => None,
+                    Err(RecvError::Closed) => break,
+                },
+                // The repo may have turned private, and its bundle must go.
+                result = withheld.recv() => match result {
+                    Ok(rid) => Some(rid),
+                    Err(RecvError::Lagged(_)) => None,
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            let bundles = self.clone();
+            let profile = profile.clone();
+            let result = tokio::task::spawn_blocking(move || match rid {
+                Some(rid) => bundles.refresh(&profile, rid),
+                None => bundles.refresh_all(&profile),
+            })
+            .await;
+
+            match result {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => tracing::warn!("Failed to refresh bundles: {e}"),
+                Err(e) => tracing::error!("Bundle refresh panicked: {e}"),
+            }
+        }
+    }
+
+    fn remove(&self, rid: &RepoId) -> io::Result<()> {
+        let old = self
+            .table
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(rid);
+
+        match old {
+            Some(old) => fs::remove_file(old.path),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A repo's directory name: its RID, without the `rad:` prefix.
+fn dir_name(rid: &RepoId) -> String {
+    let rid = rid.to_string();
+    rid.trim_start_matches("rad:").to_owned()
+}
+
+/// A repo's canonical branches and tags, with the commits they point to.
+fn canonical_refs(repo: &Repository, doc: &Doc) -> io::Result<Vec<(RefString, git::Oid)>> {
+    let refs =
+        crate::api::canonical_refs(repo, doc).map_err(|e| io::Error::other(e.to_string()))?;
+    let tags = refs
+        .tags
+        .into_iter()
+        .map(|(refname, tag)| (refname, tag.commit));
+
+    Ok(refs.refs.into_iter().chain(tags).collect())
+}
+
+/// Hash of a repo's canonical refs, or `None` if it has none.
+fn fingerprint(refs: &[(RefString, git::Oid)]) -> Result<Option<String>, git::raw::Error> {
+    if refs.is_empty() {
+        return Ok(None);
+    }
+    let mut lines = refs
+        .iter()
+        .map(|(refname, oid)| format!("{oid} {refname}\n"))
+        .collect::<Vec<_>>();
+    lines.sort();
+
+    let oid = git::raw::Oid::hash_object(git::raw::ObjectType::Blob, lines.concat().as_bytes())?;
+    Ok(Some(oid.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::test::{self, RID, RID_PRIVATE};
+
+    #[test]
+    fn bundles_follow_canonical_refs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let profile = ctx.profile();
+        let rid = RID.parse().unwrap();
+        let bundles = Bundles::open(&tmp.path().join("bundles")).unwrap();
+
+        bundles.refresh(profile, rid).unwrap();
+        bundles
+            .refresh(profile, RID_PRIVATE.parse().unwrap())
+            .unwrap();
+        let bundle = bundles.get(&rid).unwrap();
+        assert!(bundle.path.exists());
+        assert!(bundles.get(&RID_PRIVATE.parse().unwrap()).is_none());
+
+        let verify = Command::new("git")
+            .arg("--git-dir")
+            .arg(paths::repository(&profile.storage, &rid))
+            .args(["bundle", "verify", "--quiet"])
+            .arg(&bundle.path)
+            .status()
+            .unwrap();
+        assert!(verify.success());
+
+        // Unchanged refs don't rebuild, and a restart picks the bundle up.
+        bundles.refresh(profile, rid).unwrap();
+        let reopened = Bundles::open(&tmp.path().join("bundles")).unwrap();
+        assert_eq!(reopened.get(&rid).unwrap().path, bundle.path);
+
+        // A moved canonical branch does.
+        let repo = git::raw::Repository::open(paths::repository(&profile.storage, &rid)).unwrap();
+        let parent = git::raw::Oid::from_str(test::PARENT).unwrap();
+        repo.reference("refs/heads/master", parent, true, "test")
+            .unwrap();
+        bundles.refresh(profile, rid).unwrap();
+        let rebuilt = bundles.get(&rid).unwrap();
+        assert_ne!(rebuilt.fingerprint, bundle.fingerprint);
+        assert!(rebuilt.path.exists());
+        assert!(!bundle.path.exists());
+    }
+}
@@ -1,22 +1,544 @@
+use std::collections::BTreeMap;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 
+use axum::body::Bytes;
+use axum::response::Response;
 use lru::LruCache;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::Mutex;
 
 use radicle::prelude::RepoId;
-use radicle_surf::Oid;
 
+use crate::api::events::Events;
+use crate::api::RADICLE_VERSION;
+use crate::axum_extra::ETag;
+
+pub mod disk;
+
+/// Node events after which a repo's mutable responses are dropped. Seeds
+/// are included since the repo info reports the number of seeds, and local
+/// announcements since they follow changes made on the node itself, e.g. a
+/// push or a new issue.
+pub const INVALIDATING_EVENTS: [&str; 5] = [
+    "refsFetched",
+    "localRefsAnnounced",
+    "canonicalRefUpdated",
+    "seedDiscovered",
+    "seedDropped",
+];
+
+/// The endpoints that opted into response caching.
+///
+/// Responses of immutable endpoints are fully determined by the commit or
+/// tree they're keyed by. Responses of mutable endpoints depend on the repo's
+/// refs, and are dropped whenever the node reports that those changed. Commits
+/// are mutable, since they list the branches containing them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Endpoint {
+    Tree,
+    Commit,
+    Diff,
+    Blob,
+    Readme,
+    Stats,
+    Repo,
+    Remotes,
+    Issues,
+    Patches,
+}
+
+impl Endpoint {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tree => "tree",
+            Self::Commit => "commit",
+            Self::Diff => "diff",
+            Self::Blob => "blob",
+            Self::Readme => "readme",
+            Self::Stats => "stats",
+            Self::Repo => "repo",
+            Self::Remotes => "remotes",
+            Self::Issues => "issues",
+            Self::Patches => "patches",
+        }
+    }
+
+    /// Whether responses of this endpoint can change for the same key.
+    pub fn is_mutable(&self) -> bool {
+        matches!(
+            self,
+            Self::Commit | Self::Repo | Self::Remotes | Self::Issues | Self::Patches
+        )
+    }
+}
+
+/// A cached response's key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    pub endpoint: Endpoint,
+    pub rid: RepoId,
+    /// The immutable resource within the repo, e.g. `<sha>/<path>`.
+    pub resource: String,
+}
+
+impl Key {
+    pub fn new(endpoint: Endpoint, rid: RepoId, resource: impl ToString) -> Self {
+        Self {
+            endpoint,
+            rid,
+            resource: resource.to_string(),
+        }
+    }
+
+    /// A textual form of the key, unique to it. It includes the server
+    /// version, since responses persisted by one version may not have the
+    /// shape another produces.
+    pub fn id(&self) -> String {
+        format!(
+            "{RADICLE_VERSION}-{}/{}/{}/{}",
+            env!("GIT_HEAD"),
+            self.endpoint.as_str(),
+            self.rid,
+            self.resource
+        )
+    }
+
+    /// Approximate memory used by the key.
+    fn size(&self) -> usize {
+        std::mem::size_of::<Self>() + self.resource.len()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Counters {
+    pub hits: u64,
+    pub misses: u64,
+    /// Hits served from the cache directory, included in `hits`.
+    pub disk_hits: u64,
+    /// Entries dropped because the underlying repo changed.
+    pub invalidations: u64,
+}
+
+/// A cached response body, along with its entity tag for mutable endpoints.
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub body: Bytes,
+    pub etag: Option<ETag>,
+}
+
+impl Entry {
+    pub fn new(body: Bytes, etag: Option<ETag>) -> Self {
+        Self { body, etag }
+    }
+
+    /// Approximate memory used by the entry.
+    fn size(&self) -> usize {
+        self.body.len() + self.etag.as_ref().map_or(0, |etag| etag.as_str().len())
+    }
+}
+
+/// Cache usage, as reported by `/stats`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    /// Bytes used by cached responses.
+    pub size: usize,
+    /// Byte budget of the cache.
+    pub capacity: usize,
+    pub entries: usize,
+    pub endpoints: BTreeMap<Endpoint, Counters>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disk: Option<disk::Stats>,
+}
+
+/// The outcome of looking up a mutable response.
+pub enum Lookup {
+    Hit(Response),
+    /// Not cached: the response should be computed and handed back along
+    /// with the ticket, to be cached if nothing changed in the meantime.
+    Miss(Ticket),
+}
+
+/// A pending mutable response, see [`Lookup::Miss`].
+pub struct Ticket {
+    pub(crate) key: Key,
+    /// Cache generation at lookup time, if the cache could be used.
+    pub(crate) generation: Option<u64>,
+}
+
+impl Ticket {
+    /// A ticket for a response that mustn't be cached.
+    pub fn uncached(key: Key) -> Self {
+        Self {
+            key,
+            generation: None,
+        }
+    }
+}
+
+struct Inner {
+    entries: LruCache<Key, Entry>,
+    size: usize,
+    counters: BTreeMap<Endpoint, Counters>,
+    /// Number of invalidations so far.
+    generation: u64,
+}
+
+impl Inner {
+    fn insert(&mut self, capacity: usize, key: Key, entry: Entry) {
+        let size = key.size() + entry.size();
+        if size > capacity {
+            return;
+        }
+        if let Some((key, entry)) = self.entries.push(key, entry) {
+            self.size -= key.size() + entry.size();
+        }
+        self.size += size;
+
+        while self.size > capacity {
+            let Some((key, entry)) = self.entries.pop_lru() else {
+                break;
+            };
+            self.size -= key.size() + entry.size();
+        }
+    }
+}
+
+/// A cache of serialized JSON responses, bounded by the total size of the
+/// cached responses rather than by their number. Least recently used
+/// responses are evicted first.
+///
+/// Immutable responses can additionally be kept in a cache directory, from
+/// which they're read back into memory after a restart or an eviction.
 #[derive(Clone)]
 pub struct Cache {
-    pub tree: Arc<Mutex<LruCache<(RepoId, Oid, String), serde_json::Value>>>,
+    inner: Arc<Mutex<Inner>>,
+    capacity: usize,
+    disk: Option<Arc<disk::Disk>>,
 }
 
 impl Cache {
-    /// Creates a new cache of the given size.
+    /// Creates a new cache with a budget of `size` bytes.
     pub fn new(size: NonZeroUsize) -> Self {
         Cache {
-            tree: Arc::new(Mutex::new(LruCache::new(size))),
+            inner: Arc::new(Mutex::new(Inner {
+                entries: LruCache::unbounded(),
+                size: 0,
+                counters: BTreeMap::new(),
+                generation: 0,
+            })),
+            capacity: size.get(),
+            disk: None,
+        }
+    }
+
+    /// Keep immutable responses in a cache directory too.
+    pub fn with_disk(mut self, disk: disk::Disk) -> Self {
+        self.disk = Some(Arc::new(disk));
+        self
+    }
+
+    /// Get a cached response, counting a hit or a miss for its endpoint.
+    pub async fn get(&self, key: &Key) -> Option<Entry> {
+        let mut entry = self.inner.lock().await.entries.get(key).cloned();
+        let mut from_disk = false;
+
+        if entry.is_none() {
+            if let Some(body) = self.read_disk(key).await {
+                let hit = Entry::new(body, None);
+                self.inner
+                    .lock()
+                    .await
+                    .insert(self.capacity, key.clone(), hit.clone());
+
+                entry = Some(hit);
+                from_disk = true;
+            }
+        }
+        let mut inner = self.inner.lock().await;
+        let counters = inner.counters.entry(key.endpoint).or_default();
+
+        if entry.is_some() {
+            counters.hits += 1;
+            counters.disk_hits += u64::from(from_disk);
+        } else {
+            counters.misses += 1;
+        }
+        entry
+    }
+
+    /// Cache a response, evicting the least recently used responses until
+    /// it fits. Responses larger than the whole budget aren't cached.
+    /// Immutable responses are also written to the cache directory, in the
+    /// background.
+    pub async fn put(&self, key: Key, entry: Entry) {
+        if let Some(disk) = self.disk.clone().filter(|_| !key.endpoint.is_mutable()) {
+            let (key, body) = (key.clone(), entry.body.clone());
+
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = disk.put(&key, &body) {
+                    tracing::warn!("Failed to write {} to the cache directory: {e}", key.id());
+                }
+            });
         }
+        self.inner.lock().await.insert(self.capacity, key, entry);
+    }
+
+    async fn read_disk(&self, key: &Key) -> Option<Bytes> {
+        let disk = self.disk.clone().filter(|_| !key.endpoint.is_mutable())?;
+        let key = key.clone();
+
+        tokio::task::spawn_blocking(move || disk.get(&key))
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Cache a mutable response, unless the cache was invalidated after
+    /// `generation`: the response may have been computed from refs that
+    /// changed since.
+    pub async fn put_since(&self, key: Key, entry: Entry, generation: u64) {
+        let mut inner = self.inner.lock().await;
+        if inner.generation == generation {
+            inner.insert(self.capacity, key, entry);
+        }
+    }
+
+    /// The current generation, to pass to [`Cache::put_since`].
+    pub async fn generation(&self) -> u64 {
+        self.inner.lock().await.generation
+    }
+
+    /// Drop the mutable responses of `rid`, or of every repo if `rid` is
+    /// `None`. Immutable responses stay valid no matter what happens to the
+    /// repo's refs.
+    pub async fn invalidate(&self, rid: Option<RepoId>) {
+        let mut inner = self.inner.lock().await;
+        inner.generation += 1;
+
+        let stale = inner
+            .entries
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.endpoint.is_mutable() && rid.is_none_or(|rid| key.rid == rid))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for key in stale {
+            if let Some(entry) = inner.entries.pop(&key) {
+                inner.size -= key.size() + entry.size();
+                inner
+                    .counters
+                    .entry(key.endpoint)
+                    .or_default()
+                    .invalidations += 1;
+            }
+        }
+    }
+
+    /// Drop mutable responses as the node reports changes to repos, until
+    /// the process exits. Everything mutable is dropped whenever the
+    /// connection to the node changes, since events may have been missed.
+    pub async fn follow(self, events: Events) {
+        let mut connection = events.connection();
+        let (_, mut rx) = events.subscribe(None);
+
+        loop {
+            tokio::select! {
+                result = rx.recv() => match result {
+                    Ok(envelope) => {
+                        if INVALIDATING_EVENTS.contains(&envelope.kind.as_str()) {
+                            self.invalidate(Some(envelope.rid)).await;
+                        }
+                    }
+                    Err(RecvError::Lagged(_)) => self.invalidate(None).await,
+                    Err(RecvError::Closed) => break,
+                },
+                result = connection.changed() => {
+                    if result.is_err() {
+                        break;
+                    }
+                    self.invalidate(None).await;
+                }
+            }
+        }
+    }
+
+    pub async fn stats(&self) -> Stats {
+        let inner = self.inner.lock().await;
+
+        Stats {
+            size: inner.size,
+            capacity: self.capacity,
+            entries: inner.entries.len(),
+            endpoints: inner.counters.clone(),
+            disk: self.disk.as_ref().map(|disk| disk.stats()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn key(endpoint: Endpoint, resource: &str) -> Key {
+        let rid = RepoId::from_str("rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp").unwrap();
+        Key::new(endpoint, rid, resource)
+    }
+
+    fn body(len: usize) -> Entry {
+        Entry::new(Bytes::from(vec![0; len]), None)
+    }
+
+    #[tokio::test]
+    async fn cache_is_bounded_by_bytes() {
+        let entry = key(Endpoint::Tree, "a").size() + 100;
+        let cache = Cache::new(NonZeroUsize::new(entry * 2).unwrap());
+
+        cache.put(key(Endpoint::Tree, "a"), body(100)).await;
+        cache.put(key(Endpoint::Tree, "b"), body(100)).await;
+        assert!(cache.get(&key(Endpoint::Tree, "a")).await.is_some());
+
+        // "b" is now the least recently used.
+        cache.put(key(Endpoint::Tree, "c"), body(100)).await;
+        assert!(cache.get(&key(Endpoint::Tree, "a")).await.is_some());
+        assert!(cache.get(&key(Endpoint::Tree, "b")).await.is_none());
+        assert!(cache.get(&key(Endpoint::Tree, "c")).await.is_some());
+
+        // Too large to ever fit.
+        cache.put(key(Endpoint::Diff, "d"), body(entry * 2)).await;
+        assert!(cache.get(&key(Endpoint::Diff, "d")).await.is_none());
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.size, entry * 2);
+        assert_eq!(stats.entries, 2);
+        assert_eq!(
+            stats.endpoints[&Endpoint::Tree],
+            Counters {
+                hits: 3,
+                misses: 1,
+                disk_hits: 0,
+                invalidations: 0,
+            }
+        );
+        assert_eq!(
+            stats.endpoints[&Endpoint::Diff],
+            Counters {
+                hits: 0,
+                misses: 1,
+                disk_hits: 0,
+                invalidations: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn invalidation_discards_pending_entries() {
+        let cache = Cache::new(NonZeroUsize::new(4096).unwrap());
+        let generation = cache.generation().await;
+
+        cache.invalidate(None).await;
+        cache
+            .put_since(key(Endpoint::Repo, "a"), body(1), generation)
+            .await;
+        assert!(cache.get(&key(Endpoint::Repo, "a")).await.is_none());
+
+        let generation = cache.generation().await;
+        cache
+            .put_since(key(Endpoint::Repo, "a"), body(1), generation)
+            .await;
+        assert!(cache.get(&key(Endpoint::Repo, "a")).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn local_changes_invalidate_entries() {
+        let cache = Cache::new(NonZeroUsize::new(4096).unwrap());
+        let events = Events::default();
+        let rid = key(Endpoint::Issues, "").rid;
+        tokio::spawn(cache.clone().follow(events.clone()));
+
+        cache.put(key(Endpoint::Issues, ""), body(1)).await;
+        // Published until the follower has subscribed and caught one.
+        tokio::time::timeout(std::time::Duration::from_secs(10), async {
+            while cache.get(&key(Endpoint::Issues, "")).await.is_some() {
+                events.publish(rid, serde_json::json!({ "type": "localRefsAnnounced" }));
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("entry wasn't invalidated");
+    }
+
+    #[tokio::test]
+    async fn invalidation_keeps_immutable_entries() {
+        let cache = Cache::new(NonZeroUsize::new(4096).unwrap());
+        let other = Key::new(
+            Endpoint::Issues,
+            RepoId::from_str("rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5").unwrap(),
+            "",
+        );
+
+        cache.put(key(Endpoint::Tree, "a"), body(1)).await;
+        cache.put(key(Endpoint::Issues, "a"), body(1)).await;
+        cache.put(key(Endpoint::Issues, "b"), body(1)).await;
+        cache.put(other.clone(), body(1)).await;
+
+        cache.invalidate(Some(key(Endpoint::Issues, "a").rid)).await;
+        assert!(cache.get(&key(Endpoint::Tree, "a")).await.is_some());
+        assert!(cache.get(&key(Endpoint::Issues, "a")).await.is_none());
+        assert!(cache.get(&key(Endpoint::Issues, "b")).await.is_none());
+        assert!(cache.get(&other).await.is_some());
+
+        cache.invalidate(None).await;
+        assert!(cache.get(&other).await.is_none());
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.endpoints[&Endpoint::Issues].invalidations, 3);
+    }
+
+    #[tokio::test]
+    async fn immutable_entries_are_read_back_from_disk() {
+        let tmp = tempfile::tempdir().unwrap();
+        let open = || {
+            let disk = disk::Disk::open(tmp.path(), 4096).unwrap();
+            Cache::new(NonZeroUsize::new(4096).unwrap()).with_disk(disk)
+        };
+        let cache = open();
+
+        cache.put(key(Endpoint::Tree, "a"), body(1)).await;
+        cache.put(key(Endpoint::Repo, "a"), body(1)).await;
+        // Disk writes happen in the background.
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while cache.stats().await.disk.unwrap().entries == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // As if restarted.
+        let cache = open();
+        assert!(cache.get(&key(Endpoint::Tree, "a")).await.is_some());
+        assert!(cache.get(&key(Endpoint::Tree, "a")).await.is_some());
+        assert!(cache.get(&key(Endpoint::Repo, "a")).await.is_none());
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.disk.unwrap().entries, 1);
+        assert_eq!(
+            stats.endpoints[&Endpoint::Tree],
+            Counters {
+                hits: 2,
+                misses: 0,
+                disk_hits: 1,
+                invalidations: 0,
+            }
+        );
     }
 }
@@ -0,0 +1,353 @@
+use std::fs;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use axum::body::Bytes;
+use lru::LruCache;
+use serde::Serialize;
+
+use radicle::git;
+
+use super::Key;
+
+/// Bumped whenever the file format or key layout changes, so that entries
+/// written by other versions are never read. Keys hold the server version,
+/// so upgrades don't need to bump it: stale entries are simply never hit,
+/// and eventually evicted.
+const LAYOUT: &str = "v1";
+/// First line of every cached file.
+const MAGIC: &[u8] = b"radicle-httpd response v1\n";
+/// Where entries are written before being moved into place.
+const STAGING: &str = "tmp";
+
+/// Disk usage, as reported by `/stats`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    /// Bytes used by cached files.
+    pub size: u64,
+    /// Byte budget of the directory.
+    pub capacity: u64,
+    pub entries: usize,
+}
+
+struct Index {
+    /// Cached files by name, with their size.
+    files: LruCache<String, u64>,
+    size: u64,
+}
+
+/// A directory of immutable responses, bounded by the total size of its
+/// files. It outlives the process, so a restarted server doesn't have to
+/// recompute every diff and tree.
+///
+/// Entries are written to a staging file and renamed into place once
+/// complete, and each file records its key and length, so neither a crash
+/// mid-write nor a hash collision can ever serve a wrong response. The
+/// least recently used order is kept in file modification times.
+pub struct Disk {
+    root: PathBuf,
+    capacity: u64,
+    index: Mutex<Index>,
+}
+
+impl Disk {
+    /// Open the cache directory at `path`, creating it if needed, and evict
+    /// entries until it fits in `capacity` bytes.
+    pub fn open(path: &Path, capacity: u64) -> io::Result<Self> {
+        let root = path.join(LAYOUT);
+        let staging = root.join(STAGING);
+
+        // Anything left in staging is from an interrupted write.
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir_all(&staging)?;
+
+        let mut files = Vec::new();
+        for dir in fs::read_dir(&root)? {
+            let dir = dir?;
+            if dir.file_name() == STAGING || !dir.file_type()?.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(dir.path())? {
+                let file = file?;
+                let meta = file.metadata()?;
+                let (Some(prefix), Some(rest)) =
+                    (dir.file_name().to_str(), file.file_name().to_str())
+                else {
+                    continue;
+                };
+                let name = format!("{prefix}{rest}");
+                let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+
+                files.push((modified, name, meta.len()));
+            }
+        }
+        files.sort();
+
+        let mut index = Index {
+            files: LruCache::unbounded(),
+            size: 0,
+        };
+        for (_, name, size) in files {
+            index.files.push(name, size);
+            index.size += size;
+        }
+        let disk = Self {
+            root,
+            capacity,
+            index: Mutex::new(index),
+        };
+        disk.evict(&mut disk.index.lock().unwrap_or_else(|e| e.into_inner()));
+
+        Ok(disk)
+    }
+
+    /// Read a cached response. Unreadable or corrupt entries are removed.
+    pub fn get(&self, key: &Key) -> Option<Bytes> {
+        let name = file_name(key);
+        let path = self.path(&name);
+        // Also marks the entry as recently used.
+        self.index
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .files
+            .get(&name)?;
+
+        match fs::read(&path).map(|file| decode(key, file)) {
+            Ok(Some(body)) => {
+                // Keeps the recency across restarts. Not worth failing for.
+                fs::File::options()
+                    .append(true)
+                    .open(&path)
+                    .and_then(|f| f.set_modified(SystemTime::now()))
+                    .ok();
+                Some(body)
+            }
+            Ok(None) | Err(_) => {
+                tracing::warn!("Removing corrupt cache entry {}", path.display());
+                let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some(size) = index.files.pop(&name) {
+                    index.size -= size;
+                }
+                fs::remove_file(&path).ok();
+                None
+            }
+        }
+    }
+
+    /// Cache a response, evicting the least recently used files until it
+    /// fits. Responses larger than the whole budget aren't cached.
+    pub fn put(&self, key: &Key, body: &[u8]) -> io::Result<()> {
+        let name = file_name(key);
+        if self
+            .index
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .files
+            .contains(&name)
+        {
+            return Ok(());
+        }
+        let file = encode(key, body);
+        let size = file.len() as u64;
+        if size > self.capacity {
+            return Ok(());
+        }
+        let path = self.path(&name);
+        let staged = self
+            .root
+            .join(STAGING)
+            .join(uuid::Uuid::new_v4().to_string());
+
+        let result = (|| {
+            let mut f = fs::File::create(&staged)?;
+            f.write_all(&file)?;
+            f.sync_data()?;
+
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::rename(&staged, &path)
+        })();
+        if let Err(e) = result {
+            fs::remove_file(&staged).ok();
+            return Err(e);
+        }
+
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        if index.files.put(name, size).is_none() {
+            index.size += size;
+        }
+        self.evict(&mut index);
+
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Stats {
+        let index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+
+        Stats {
+            size: index.size,
+            capacity: self.capacity,
+            entries: index.files.len(),
+        }
+    }
+
+    fn evict(&self, index: &mut Index) {
+        while index.size > self.capacity {
+            let Some((name, size)) = index.files.pop_lru() else {
+                break;
+            };
+            index.size -= size;
+
+            if let Err(e) = fs::remove_file(self.path(&name)) {
+                if e.kind() != io::ErrorKind::NotFound {
+                    tracing::warn!("Failed to evict cache entry {name}: {e}");
+                }
+            }
+        }
+    }
+
+    /// Files are spread over subdirectories by the first two characters of
+    /// their name, like git's loose objects.
+    fn path(&self, name: &str) -> PathBuf {
+        let (dir, file) = name.split_at(2);
+        self.root.join(dir).join(file)
+    }
+}
+
+/// The name of a key's file: a hash of the key, which may contain arbitrary
+/// paths.
+fn file_name(key: &Key) -> String {
+    let id = key.id();
+    let oid = git::raw::Oid::hash_object(git::raw::ObjectType::Blob, id.as_bytes());
+
+    // SAFETY: Hashing an in-memory buffer doesn't fail.
+    #[allow(clippy::unwrap_used)]
+    oid.unwrap().to_string()
+}
+
+/// A cached file is the magic line, the key and the body length on a line
+/// each, then the body.
+fn encode(key: &Key, body: &[u8]) -> Vec<u8> {
+    let mut file = MAGIC.to_vec();
+    file.extend_from_slice(format!("{}\n{}\n", key.id(), body.len()).as_bytes());
+    file.extend_from_slice(body);
+    file
+}
+
+/// Decode a cached file, returning `None` if it's not a complete entry for
+/// `key`.
+fn decode(key: &Key, file: Vec<u8>) -> Option<Bytes> {
+    let rest = file.strip_prefix(MAGIC)?;
+    let rest = rest
+        .strip_prefix(key.id().as_bytes())?
+        .strip_prefix(b"\n")?;
+    let newline = rest.iter().position(|b| *b == b'\n')?;
+    let len = std::str::from_utf8(&rest[..newline])
+        .ok()?
+        .parse::<usize>()
+        .ok()?;
+    let body = &rest[newline + 1..];
+
+    (body.len() == len).then(|| Bytes::copy_from_slice(body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use radicle::prelude::RepoId;
+
+    use super::*;
+    use crate::cache::Endpoint;
+
+    fn key(resource: &str) -> Key {
+        let rid = RepoId::from_str("rad:z4FucBZHZMCsxTyQE1dfE2YR59Qbp").unwrap();
+        Key::new(Endpoint::Diff, rid, resource)
+    }
+
+    #[test]
+    fn entries_survive_reopening() {
+        let tmp = tempfile::tempdir().unwrap();
+        let disk = Disk::open(tmp.path(), 4096).unwrap();
+
+        disk.put(&key("a..b"), b"{}").unwrap();
+        assert_eq!(disk.get(&key("a..b")).unwrap(), &b"{}"[..]);
+        assert!(disk.get(&key("b..c")).is_none());
+        drop(disk);
+
+        let disk = Disk::open(tmp.path(), 4096).unwrap();
+        assert_eq!(disk.stats().entries, 1);
+        assert_eq!(disk.get(&key("a..b")).unwrap(), &b"{}"[..]);
+    }
+
+    #[test]
+    fn partial_writes_are_ignored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let disk = Disk::open(tmp.path(), 4096).unwrap();
+        let path = disk.path(&file_name(&key("a..b")));
+
+        disk.put(&key("a..b"), b"[1, 2, 3]").unwrap();
+        drop(disk);
+
+        // A torn write, and a leftover staging file.
+        let file = fs::read(&path).unwrap();
+        fs::write(&path, &file[..file.len() - 2]).unwrap();
+        fs::write(tmp.path().join(LAYOUT).join(STAGING).join("x"), b"").unwrap();
+
+        let disk = Disk::open(tmp.path(), 4096).unwrap();
+        assert!(disk.get(&key("a..b")).is_none());
+        assert!(!path.exists());
+        assert_eq!(disk.stats().entries, 0);
+        assert_eq!(
+            fs::read_dir(tmp.path().join(LAYOUT).join(STAGING))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn entries_of_other_versions_are_ignored() {
+        let tmp = tempfile::tempdir().unwrap();
+        let disk = Disk::open(tmp.path(), 4096).unwrap();
+        let id = key("a..b").id();
+        let (_, unversioned) = id.split_once('/').unwrap();
+
+        // What a server without the version in its keys would have written.
+        let oid = git::raw::Oid::hash_object(git::raw::ObjectType::Blob, unversioned.as_bytes());
+        let name = oid.unwrap().to_string();
+        let mut file = MAGIC.to_vec();
+        file.extend_from_slice(format!("{unversioned}\n2\n{{}}").as_bytes());
+        fs::create_dir_all(disk.path(&name).parent().unwrap()).unwrap();
+        fs::write(disk.path(&name), file).unwrap();
+        drop(disk);
+
+        let disk = Disk::open(tmp.path(), 4096).unwrap();
+        assert_eq!(disk.stats().entries, 1);
+        assert!(disk.get(&key("a..b")).is_none());
+    }
+
+    #[test]
+    fn disk_is_bounded_by_bytes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let entry = encode(&key("a"), &[0; 100]).len() as u64;
+        let disk = Disk::open(tmp.path(), entry * 2).unwrap();
+
+        disk.put(&key("a"), &[0; 100]).unwrap();
+        disk.put(&key("b"), &[0; 100]).unwrap();
+        assert!(disk.get(&key("a")).is_some());
+
+        // "b" is now the least recently used.
+        disk.put(&key("c"), &[0; 100]).unwrap();
+        assert!(disk.get(&key("a")).is_some());
+        assert!(disk.get(&key("b")).is_none());
+        assert!(disk.get(&key("c")).is_some());
+        assert_eq!(disk.stats().size, entry * 2);
+    }
+}
@@ -3,6 +3,9 @@ use std::process::ExitStatus;
 use axum::http;
 use axum::response::{IntoResponse, Response};
 
+/// Seconds a git client is asked to wait when every backend is busy.
+const GIT_RETRY_AFTER_SECS: &str = "10";
+
 /// Errors relating to the Git backend.
 #[derive(Debug, thiserror::Error)]
 pub enum GitError {
@@ -18,6 +21,14 @@ pub enum GitError {
     #[error("service '{0}' not available")]
     ServiceUnavailable(&'static str),
 
+    /// Every git backend process is in use.
+    #[error("too many concurrent git requests")]
+    Busy,
+
+    /// The git backend took too long.
+    #[error("git-http-backend: timed out")]
+    Timeout,
+
     /// Invalid identifier.
     #[error("invalid radicle identifier: {0}")]
     Id(#[from] radicle::identity::IdError),
@@ -30,6 +41,14 @@ pub enum GitError {
     #[error("repository: {0}")]
     Repository(#[from] radicle::storage::RepositoryError),
 
+    /// A blocking task failed to complete.
+    #[error(transparent)]
+    Task(#[from] tokio::task::JoinError),
+
+    /// The canonical refs couldn't be computed.
+    #[error("canonical refs: {0}")]
+    CanonicalRefs(String),
+
     /// Git backend error.
     #[error("git-http-backend: exited with code {0}")]
     BackendExited(ExitStatus),
@@ -50,7 +69,10 @@ pub enum GitError {
 impl GitError {
     pub fn status(&self) -> http::StatusCode {
         match self {
-            GitError::ServiceUnavailable(_) => http::StatusCode::SERVICE_UNAVAILABLE,
+            GitError::ServiceUnavailable(_) | GitError::Busy => {
+                http::StatusCode::SERVICE_UNAVAILABLE
+            }
+            GitError::Timeout => http::StatusCode::GATEWAY_TIMEOUT,
             GitError::Id(_) => http::StatusCode::NOT_FOUND,
             GitError::NotFound => http::StatusCode::NOT_FOUND,
             _ => http::StatusCode::INTERNAL_SERVER_ERROR,
@@ -60,6 +82,15 @@ impl GitError {
 
 impl IntoResponse for GitError {
     fn into_response(self) -> Response {
+        if let GitError::Busy = self {
+            tracing::warn!("{}", self);
+
+            return (
+                self.status(),
+                [(http::header::RETRY_AFTER, GIT_RETRY_AFTER_SECS)],
+            )
+                .into_response();
+        }
         tracing::error!("{}", self);
 
         self.status().into_response()
@@ -101,6 +132,18 @@ pub enum RawError {
     #[error(transparent)]
     SurfFile(#[from] radicle_surf::fs::error::File),
 
+    /// Profile error.
+    #[error(transparent)]
+    Profile(#[from] radicle::profile::Error),
+
+    /// Issue cache error.
+    #[error(transparent)]
+    CacheIssue(#[from] radicle::cob::issue::cache::Error),
+
+    /// Patch cache error.
+    #[error(transparent)]
+    CachePatch(#[from] radicle::cob::patch::cache::Error),
+
     /// The entity was not found.
     #[error("not found")]
     NotFound,
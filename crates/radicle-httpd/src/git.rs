@@ -1,41 +1,85 @@
 use std::collections::HashMap;
+use std::io;
 use std::path::Path;
 use std::process::Stdio;
 use std::str;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::body::Body;
 use axum::extract::{ConnectInfo, Path as AxumPath, RawQuery, Request, State};
-use axum::http::header::HeaderName;
-use axum::http::{HeaderMap, Method, StatusCode};
+use axum::http::header::{HeaderName, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_TYPE, ETAG, HOST};
+use axum::http::{HeaderMap, HeaderValue, Method, StatusCode};
 use axum::response::IntoResponse;
 use axum::routing::any;
 use axum::Router;
 use axum_listener::DualAddr;
 
-use futures_util::TryStreamExt;
+use futures_util::{StreamExt as _, TryStreamExt};
 use radicle::identity::RepoId;
 use radicle::node::NodeId;
 use radicle::profile::Profile;
 use radicle::storage::{ReadRepository, ReadStorage};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use tokio::process::{Child, ChildStdout, Command};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
 use tokio_util::io::{ReaderStream, StreamReader};
 use tower_http::decompression::RequestDecompressionLayer;
 
+use crate::bundles::{self, Bundles};
 use crate::error::GitError as Error;
+use crate::GitOptions;
+
+/// How long a request may wait for one of the running backends to finish
+/// before it's turned away.
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upper bound on the configured timeouts: far enough to mean "never", near
+/// enough that adding it to the current instant can't overflow.
+const MAX_TIMEOUT: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+
+/// Shared state of the git routes.
+#[derive(Clone)]
+struct Backend {
+    profile: Arc<Profile>,
+    aliases: Arc<HashMap<String, RepoId>>,
+    /// Permits to run a `git http-backend` process.
+    processes: Arc<Semaphore>,
+    /// How long a process may run.
+    timeout: Duration,
+    /// How long a process may go without producing output.
+    idle_timeout: Duration,
+    /// Prebuilt clone bundles, if enabled.
+    bundles: Option<Bundles>,
+}
+
+pub fn router(
+    profile: Arc<Profile>,
+    aliases: Arc<HashMap<String, RepoId>>,
+    options: &GitOptions,
+    bundles: Option<Bundles>,
+) -> Router {
+    let backend = Backend {
+        profile,
+        aliases,
+        bundles,
+        processes: Arc::new(Semaphore::new(options.processes.get())),
+        timeout: options.timeout.min(MAX_TIMEOUT),
+        idle_timeout: options.idle_timeout.min(MAX_TIMEOUT),
+    };
 
-pub fn router(profile: Arc<Profile>, aliases: Arc<HashMap<String, RepoId>>) -> Router {
     Router::new()
         .route(
             "/{rid}/{*path}",
             any(git_handler).layer(RequestDecompressionLayer::new()),
         )
-        .with_state((profile, aliases))
+        .with_state(backend)
 }
 
 async fn git_handler(
-    State((profile, aliases)): State<(Arc<Profile>, Arc<HashMap<String, RepoId>>)>,
+    State(backend): State<Backend>,
     AxumPath((repository, path)): AxumPath<(String, String)>,
     method: Method,
     headers: HeaderMap,
@@ -45,7 +89,7 @@ async fn git_handler(
 ) -> impl IntoResponse {
     let query = query.0.unwrap_or_default();
     let name = repository.strip_suffix(".git").unwrap_or(&repository);
-    let Some(rid) = crate::resolve_rid(name, &aliases) else {
+    let Some(rid) = crate::resolve_rid(name, &backend.aliases) else {
         return Err(Error::NotFound);
     };
 
@@ -57,8 +101,20 @@ async fn git_handler(
         }
     };
 
+    // Bundles only hold the canonical refs, so they aren't offered for
+    // clones of a single peer's namespace.
+    let bundle = match (&backend.bundles, nid) {
+        (Some(bundles), None) => bundles.get(&rid),
+        _ => None,
+    };
+    if path == bundles::BUNDLE_PATH {
+        let bundle = bundle.ok_or(Error::NotFound)?;
+        return bundle_response(&backend.profile, rid, bundle).await;
+    }
+    let bundle_uri = bundle.and_then(|_| bundle_uri(&headers, &repository));
+
     let (status, headers, body) = git_http_backend(
-        &profile, method, headers, request, remote, rid, nid, path, query,
+        &backend, method, headers, request, remote, rid, nid, path, query, bundle_uri,
     )
     .await?;
 
@@ -73,8 +129,54 @@ async fn git_handler(
     Ok::<_, Error>((status, response_headers, body))
 }
 
-async fn git_http_backend(
+/// Serve a prebuilt bundle.
+async fn bundle_response(
     profile: &Profile,
+    rid: RepoId,
+    bundle: bundles::Bundle,
+) -> Result<(StatusCode, HeaderMap, Body), Error> {
+    // The bundle may predate the repo becoming private.
+    let doc = profile.storage.repository(rid)?.identity_doc()?;
+    if doc.visibility().is_private() {
+        return Err(Error::NotFound);
+    }
+    let file = tokio::fs::File::open(&bundle.path).await?;
+    let len = file.metadata().await?.len();
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/x-git-bundle"),
+    );
+    headers.insert(CONTENT_LENGTH, HeaderValue::from(len));
+    headers.insert(ETAG, format!("\"{}\"", bundle.fingerprint).parse()?);
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+
+    Ok((
+        StatusCode::OK,
+        headers,
+        Body::from_stream(ReaderStream::new(file)),
+    ))
+}
+
+/// The absolute URL of the bundle of the repo at `repository`, as seen by the
+/// client, for the `bundle-uri` capability.
+fn bundle_uri(headers: &HeaderMap, repository: &str) -> Option<String> {
+    let host = headers.get(HOST)?.to_str().ok()?;
+    let scheme = headers
+        .get("X-Forwarded-Proto")
+        .and_then(|x| x.to_str().ok())
+        .filter(|p| *p == "https")
+        .unwrap_or("http");
+
+    Some(format!(
+        "{scheme}://{host}/{repository}/{}",
+        bundles::BUNDLE_PATH
+    ))
+}
+
+async fn git_http_backend(
+    backend: &Backend,
     method: Method,
     headers: HeaderMap,
     request: Request,
@@ -83,18 +185,26 @@ async fn git_http_backend(
     nid: Option<NodeId>,
     path: &str,
     query: String,
+    bundle_uri: Option<String>,
 ) -> Result<(StatusCode, HashMap<String, Vec<String>>, Body), Error> {
+    let profile = &backend.profile;
     let git_dir = radicle::storage::git::paths::repository(&profile.storage, &id);
     let content_type = headers
         .get("Content-Type")
         .and_then(|x| x.to_str().ok())
         .unwrap_or_default();
+    // Lets clients negotiate protocol v2, see `gitprotocol-http(5)`.
+    let protocol = headers
+        .get("Git-Protocol")
+        .and_then(|x| x.to_str().ok())
+        .filter(|p| !p.is_empty());
 
-    // Don't allow cloning of private repositories.
-    let doc = profile.storage.repository(id)?.identity_doc()?;
-    if doc.visibility().is_private() {
-        return Err(Error::NotFound);
-    }
+    // Reading the identity and computing the canonical refs hits storage, so
+    // it's kept off the async workers.
+    let hidden = {
+        let profile = profile.clone();
+        tokio::task::spawn_blocking(move || hidden_refs(&profile, id, nid)).await??
+    };
 
     // Reject push requests.
     match (path, query.as_str()) {
@@ -110,15 +220,37 @@ async fn git_http_backend(
     tracing::debug!("method: {:?}", method.as_str());
     tracing::debug!("remote: {:?}", remote);
 
+    let permit = tokio::time::timeout(QUEUE_TIMEOUT, backend.processes.clone().acquire_owned())
+        .await
+        .map_err(|_| Error::Busy)?
+        .map_err(|_| Error::Busy)?;
+    let deadline = Instant::now() + backend.timeout;
+
     let mut cmd = Command::new("git");
     if let Some(nid) = nid {
         cmd.env("GIT_NAMESPACE", nid.to_string());
     }
+    if let Some(protocol) = protocol {
+        cmd.env("GIT_PROTOCOL", protocol);
+    }
+    for hide in hidden {
+        cmd.arg("-c").arg(hide);
+    }
+    // Advertised to protocol v2 clients, which may then fetch the bundle
+    // before negotiating the rest.
+    if let Some(uri) = bundle_uri {
+        cmd.args(["-c", "uploadpack.advertiseBundleURIs=true"])
+            .args(["-c", "bundle.version=1", "-c", "bundle.mode=all"])
+            .arg("-c")
+            .arg(format!("bundle.canonical.uri={uri}"));
+    }
     let mut child = cmd
         // This is a workaround to allow fetching particular commits by their OID.
         // Otherwise, the client errors with "Server does not allow request for unadvertised object"
         // See also `REF_UNADVERTISED_NOT_ALLOWED` in Git's `fetch-pack.c` (as of 0bd2d79).
         .args(["-c", "uploadpack.allowAnySHA1InWant"])
+        // Allows partial clones, e.g. with `--filter=blob:none`.
+        .args(["-c", "uploadpack.allowFilter"])
         .arg("http-backend")
         .env("REQUEST_METHOD", method.as_str())
         .env("GIT_PROJECT_ROOT", git_dir)
@@ -132,6 +264,10 @@ async fn git_http_backend(
         .stderr(Stdio::piped())
         .stdout(Stdio::piped())
         .stdin(Stdio::piped())
+        // Puts the backend and the `upload-pack` and `pack-objects` it runs
+        // in their own group, so they can be stopped together.
+        .process_group(0)
+        .kill_on_drop(true)
         .spawn()?;
 
     let mut stdin = child.stdin.take().expect("stdin was captured");
@@ -140,11 +276,12 @@ async fn git_http_backend(
         .into_data_stream()
         .map_err(std::io::Error::other);
     let mut body = StreamReader::new(body);
-    tokio::spawn(async move {
+    let copy = tokio::spawn(async move {
         let _ = tokio::io::copy(&mut body, &mut stdin).await;
     });
+    let mut process = Process { child, copy };
 
-    let stderr = BufReader::new(child.stderr.take().expect("stderr was captured"));
+    let stderr = BufReader::new(process.child.stderr.take().expect("stderr was captured"));
     tokio::spawn(async move {
         let mut lines = stderr.lines();
         while let Ok(Some(line)) = lines.next_line().await {
@@ -152,12 +289,12 @@ async fn git_http_backend(
         }
     });
 
-    let mut stdout = BufReader::new(child.stdout.take().expect("stdout was captured"));
+    let mut stdout = BufReader::new(process.child.stdout.take().expect("stdout was captured"));
 
     let mut headers = HashMap::<String, Vec<String>>::new();
 
     let mut line = String::new();
-    while stdout.read_line(&mut line).await? != 0 {
+    while read_timeout(deadline, backend.idle_timeout, stdout.read_line(&mut line)).await?? != 0 {
         if line.ends_with("\r\n") {
             line.truncate(line.len() - 2);
         }
@@ -180,11 +317,121 @@ async fn git_http_backend(
         .and_then(|values| values.first()?.split_whitespace().next()?.parse().ok())
         .unwrap_or(StatusCode::OK);
 
-    let body = Body::from_stream(ReaderStream::new(stdout));
+    let output = Output {
+        stdout: ReaderStream::new(stdout),
+        process,
+        _permit: permit,
+        deadline,
+        idle_timeout: backend.idle_timeout,
+    };
+    let body = Body::from_stream(futures_util::stream::unfold(
+        Some(output),
+        |output| async move {
+            let mut output = output?;
+            let read = read_timeout(output.deadline, output.idle_timeout, output.stdout.next());
+
+            match read.await {
+                Ok(Some(chunk)) => Some((chunk, Some(output))),
+                Ok(None) => {
+                    // Reap the process, it's done writing.
+                    let exit = output.process.child.wait();
+                    read_timeout(output.deadline, output.idle_timeout, exit)
+                        .await
+                        .ok();
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!("git-http-backend: {e}, aborting response");
+                    Some((Err(io::Error::new(io::ErrorKind::TimedOut, e)), None))
+                }
+            }
+        },
+    ));
 
     Ok((status, headers, body))
 }
 
+/// The response body of a running backend. Dropping it, e.g. because the
+/// client went away, kills the process and frees its permit.
+struct Output {
+    stdout: ReaderStream<BufReader<ChildStdout>>,
+    process: Process,
+    _permit: OwnedSemaphorePermit,
+    deadline: Instant,
+    idle_timeout: Duration,
+}
+
+/// A spawned backend, along with the task feeding it the request body.
+///
+/// Dropping it, whether the request was abandoned by the client or by a
+/// timeout, stops the task and kills the backend's whole process group.
+struct Process {
+    child: Child,
+    copy: JoinHandle<()>,
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        self.copy.abort();
+
+        // Once the backend has been reaped, its group is gone too.
+        if let Some(pid) = self.child.id() {
+            // SAFETY: `killpg` has no memory safety requirements.
+            unsafe {
+                libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+/// Configuration hiding the refs of the repo `id` that aren't served.
+///
+/// Fails with [`Error::NotFound`] for private repos, which can't be cloned.
+/// Without a namespace, only the canonical refs are advertised.
+fn hidden_refs(profile: &Profile, id: RepoId, nid: Option<NodeId>) -> Result<Vec<String>, Error> {
+    let repo = profile.storage.repository(id)?;
+    let doc = repo.identity_doc()?;
+    if doc.visibility().is_private() {
+        return Err(Error::NotFound);
+    }
+
+    match nid {
+        Some(_) => Ok(Vec::new()),
+        None => canonical_view(&repo, &doc.doc),
+    }
+}
+
+/// Configuration hiding every ref of `repo` but its canonical ones, as
+/// computed from the identity document's rules, so that plain clones get the
+/// delegates' agreed state. Objects remain fetchable by id.
+fn canonical_view(
+    repo: &radicle::storage::git::Repository,
+    doc: &radicle::identity::Doc,
+) -> Result<Vec<String>, Error> {
+    let refs =
+        crate::api::canonical_refs(repo, doc).map_err(|e| Error::CanonicalRefs(e.to_string()))?;
+    let shown = refs.refs.keys().chain(refs.tags.keys());
+
+    // Later entries take precedence.
+    Ok(std::iter::once("transfer.hideRefs=refs".to_owned())
+        .chain(shown.map(|refname| format!("transfer.hideRefs=!{refname}")))
+        .collect())
+}
+
+/// Wait for `read`, failing if it takes longer than `idle_timeout` or goes
+/// past `deadline`.
+async fn read_timeout<T>(
+    deadline: Instant,
+    idle_timeout: Duration,
+    read: impl std::future::Future<Output = T>,
+) -> Result<T, Error> {
+    let until = deadline.min(Instant::now() + idle_timeout);
+
+    tokio::time::timeout_at(until, read)
+        .await
+        .map_err(|_| Error::Timeout)
+}
+
 #[cfg(test)]
 mod routes {
     use std::collections::HashMap;
@@ -194,18 +441,80 @@ mod routes {
 
     use axum::extract::connect_info::MockConnectInfo;
     use axum::http::StatusCode;
+    use axum::Router;
     use axum_listener::DualAddr;
     use radicle::identity::RepoId;
+    use radicle::storage::{WriteRepository as _, WriteStorage as _};
+    use tokio::process::Command;
+
+    use crate::test::{self, get, get_with_headers, RID};
+    use crate::GitOptions;
 
-    use crate::test::{self, get, RID};
+    /// Serve `app` on a local port, for real git clients.
+    async fn serve(app: Router) -> SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = app.layer(MockConnectInfo(DualAddr::Tcp(addr)));
+
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        addr
+    }
+
+    /// Run git in `dir`, returning its standard output.
+    async fn git(dir: &std::path::Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(args)
+            .output()
+            .await
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8(output.stdout).unwrap()
+    }
 
     #[tokio::test]
     async fn test_info_request() {
         let tmp = tempfile::tempdir().unwrap();
         let ctx = test::seed(tmp.path());
-        let app = super::router(ctx.profile().to_owned(), Arc::new(HashMap::new())).layer(
-            MockConnectInfo(DualAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], 8080)))),
-        );
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            &GitOptions::default(),
+            None,
+        )
+        .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
+            [0, 0, 0, 0],
+            8080,
+        )))));
+
+        let response = get(&app, format!("/{RID}.git/info/refs")).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_unbounded_timeouts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let options = GitOptions {
+            timeout: std::time::Duration::MAX,
+            idle_timeout: std::time::Duration::MAX,
+            ..GitOptions::default()
+        };
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            &options,
+            None,
+        )
+        .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
+            [0, 0, 0, 0],
+            8080,
+        )))));
 
         let response = get(&app, format!("/{RID}.git/info/refs")).await;
 
@@ -222,6 +531,8 @@ mod routes {
                 String::from("heartwood"),
                 RepoId::from_str(RID).unwrap(),
             )])),
+            &GitOptions::default(),
+            None,
         )
         .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
             [0, 0, 0, 0],
@@ -234,4 +545,207 @@ mod routes {
         let response = get(&app, "/heartwood.git/info/refs").await;
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[tokio::test]
+    async fn test_dropped_responses_free_processes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let options = GitOptions {
+            processes: std::num::NonZeroUsize::MIN,
+            ..GitOptions::default()
+        };
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            &options,
+            None,
+        )
+        .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
+            [0, 0, 0, 0],
+            8080,
+        )))));
+
+        // Each response is dropped unread, which must kill its process.
+        for _ in 0..3 {
+            let response = get(&app, format!("/{RID}.git/info/refs")).await;
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_protocol_v2() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            &GitOptions::default(),
+            None,
+        )
+        .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
+            [0, 0, 0, 0],
+            8080,
+        )))));
+
+        let response = get_with_headers(
+            &app,
+            format!("/{RID}.git/info/refs?service=git-upload-pack"),
+            &[("Git-Protocol", "version=2")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.body().await;
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("version 2"), "{body}");
+        assert!(body.contains("fetch=") && body.contains("filter"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn test_partial_and_shallow_clones() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            &GitOptions::default(),
+            None,
+        );
+        let addr = serve(app).await;
+        let url = format!("http://{addr}/{RID}.git");
+
+        let filtered = tmp.path().join("filtered");
+        git(
+            tmp.path(),
+            &[
+                "-c",
+                "protocol.version=2",
+                "clone",
+                "--filter=blob:none",
+                "--no-checkout",
+                &url,
+                filtered.to_str().unwrap(),
+            ],
+        )
+        .await;
+        // Git sets up the filter even when the server ignores it, so check that
+        // blobs were actually left out.
+        let missing = git(
+            &filtered,
+            &["rev-list", "--objects", "--all", "--missing=print"],
+        )
+        .await;
+        assert!(missing.lines().any(|line| line.starts_with('?')));
+
+        let shallow = tmp.path().join("shallow");
+        git(
+            tmp.path(),
+            &[
+                "-c",
+                "protocol.version=2",
+                "clone",
+                "--depth=1",
+                &url,
+                shallow.to_str().unwrap(),
+            ],
+        )
+        .await;
+        let count = git(&shallow, &["rev-list", "--count", "HEAD"]).await;
+        assert_eq!(count.trim(), "1");
+        assert_eq!(
+            git(&shallow, &["rev-parse", "HEAD"]).await.trim(),
+            test::HEAD
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundles() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let bundles = crate::bundles::Bundles::open(&tmp.path().join("bundles")).unwrap();
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            &GitOptions::default(),
+            Some(bundles.clone()),
+        )
+        .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
+            [0, 0, 0, 0],
+            8080,
+        )))));
+
+        let response = get(&app, format!("/{RID}.git/clone.bundle")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        bundles
+            .refresh(ctx.profile(), RID.parse().unwrap())
+            .unwrap();
+        let response = get(&app, format!("/{RID}.git/clone.bundle")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.body().await.starts_with(b"# v2 git bundle\n"));
+
+        let mut headers = axum::http::HeaderMap::new();
+        headers.insert("Host", "seed.example.com".parse().unwrap());
+        headers.insert("X-Forwarded-Proto", "https".parse().unwrap());
+        assert_eq!(
+            super::bundle_uri(&headers, "heartwood.git").unwrap(),
+            "https://seed.example.com/heartwood.git/clone.bundle"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_canonical_view() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let nid = ctx.profile().public_key;
+        let repo = ctx
+            .profile()
+            .storage
+            .repository_mut(RID.parse().unwrap())
+            .unwrap();
+        // A top-level branch that isn't canonical, as a seed operator might push.
+        repo.raw()
+            .reference(
+                "refs/heads/operator",
+                radicle::git::raw::Oid::from_str(test::PARENT).unwrap(),
+                false,
+                "test",
+            )
+            .unwrap();
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            &GitOptions::default(),
+            None,
+        )
+        .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
+            [0, 0, 0, 0],
+            8080,
+        )))));
+
+        let response = get(
+            &app,
+            format!("/{RID}.git/info/refs?service=git-upload-pack"),
+        )
+        .await;
+        let body = response.body().await;
+        let body = String::from_utf8_lossy(&body);
+        assert!(
+            body.contains(&format!("{} refs/heads/master", test::HEAD)),
+            "{body}"
+        );
+        assert!(!body.contains("refs/heads/operator"), "{body}");
+        assert!(!body.contains("refs/rad/"), "{body}");
+        assert!(!body.contains("refs/namespaces/"), "{body}");
+
+        // Peer namespaces are served as they are.
+        let response = get(
+            &app,
+            format!("/{RID}.git/{nid}/info/refs?service=git-upload-pack"),
+        )
+        .await;
+        let body = response.body().await;
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("refs/rad/sigrefs"), "{body}");
+    }
 }
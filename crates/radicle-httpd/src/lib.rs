@@ -21,7 +21,7 @@ use axum::routing::get;
 use axum::{middleware, Json, Router};
 use axum_listener::{DualAddr, DualListener};
 use hyper::body::Body as _;
-use hyper::header::CONTENT_TYPE;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE};
 use hyper::Method;
 use tower_http::cors;
 use tower_http::cors::CorsLayer;
@@ -32,19 +32,37 @@ use radicle::identity::RepoId;
 use radicle::Profile;
 
 use crate::api::RADICLE_VERSION;
+use crate::rate_limit::{Limiter, Scope};
+pub use crate::rate_limit::{Quota, TrustedProxy};
 use crate::tracing_extra::{tracing_middleware, ColoredStatus, Paint, RequestId, TracingInfo};
 
 mod api;
 mod axum_extra;
+mod bundles;
 mod cache;
 mod git;
+mod rate_limit;
 mod raw;
 #[cfg(test)]
 mod test;
 mod tracing_extra;
 
-/// Default cache HTTP size.
+/// Default response cache budget, in MiB.
 pub const DEFAULT_CACHE_SIZE: NonZeroUsize = NonZeroUsize::new(100).unwrap();
+/// Default cache directory budget, in MiB.
+pub const DEFAULT_CACHE_DIR_SIZE: NonZeroUsize = NonZeroUsize::new(1024).unwrap();
+/// Default number of blocking calls running at once, per lane.
+pub const DEFAULT_BLOCKING_LIMIT: NonZeroUsize = NonZeroUsize::new(32).unwrap();
+/// Default number of blocking calls waiting for a slot, per lane.
+pub const DEFAULT_BLOCKING_QUEUE: usize = 256;
+/// Default number of `git http-backend` processes running at once.
+pub const DEFAULT_GIT_PROCESSES: NonZeroUsize = NonZeroUsize::new(32).unwrap();
+/// Default time a `git http-backend` process may run.
+pub const DEFAULT_GIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Default time a `git http-backend` process may go without output.
+pub const DEFAULT_GIT_IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+/// Default size of the largest blob served by `/raw`, in MiB.
+pub const DEFAULT_MAX_BLOB_SIZE: NonZeroUsize = NonZeroUsize::new(1024).unwrap();
 
 /// Resolve a repo path segment to a [`RepoId`]. The segment may be either a
 /// canonical RID or one of the aliases configured via `--alias`. Returns
@@ -59,10 +77,91 @@ pub(crate) fn resolve_rid(name: &str, aliases: &HashMap<String, RepoId>) -> Opti
 pub struct Options {
     pub aliases: HashMap<String, RepoId>,
     pub listen: DualAddr,
+    /// Response cache budget in MiB. `None` disables the cache.
     pub cache: Option<NonZeroUsize>,
+    /// Persistent cache directory for immutable responses. Requires the
+    /// response cache.
+    pub cache_dir: Option<CacheDirOptions>,
     /// Search backend configuration. `None` disables search at runtime and
     /// falls back to the built-in storage walk.
     pub search: Option<SearchOptions>,
+    /// Enable the authenticated write API for issues and patches.
+    pub write: bool,
+    /// Limits on blocking work, like repository reads.
+    pub blocking: BlockingOptions,
+    /// Per-client request rate limits.
+    pub rate_limit: RateLimitOptions,
+    /// Limits on the git processes serving fetches and clones.
+    pub git: GitOptions,
+    /// Directory of prebuilt clone bundles, advertised to git clients via
+    /// `bundle-uri`. `None` disables bundles.
+    pub bundle_dir: Option<std::path::PathBuf>,
+    /// Size of the largest blob served by `/raw`, in MiB. Larger blobs get
+    /// `413 Payload Too Large`.
+    pub max_blob_size: NonZeroUsize,
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheDirOptions {
+    pub path: std::path::PathBuf,
+    /// Budget of the directory, in MiB.
+    pub size: NonZeroUsize,
+}
+
+/// Limits on the blocking work done for API requests. Cheap and expensive
+/// routes are limited separately, each with these values.
+#[derive(Debug, Clone)]
+pub struct BlockingOptions {
+    /// How many blocking calls may run at once.
+    pub limit: NonZeroUsize,
+    /// How many blocking calls may wait for a slot before requests are
+    /// turned away with `503 Service Unavailable`.
+    pub queue: usize,
+}
+
+impl Default for BlockingOptions {
+    fn default() -> Self {
+        Self {
+            limit: DEFAULT_BLOCKING_LIMIT,
+            queue: DEFAULT_BLOCKING_QUEUE,
+        }
+    }
+}
+
+/// Limits on `git http-backend` processes. Requests that find every process
+/// busy wait briefly, then get `503 Service Unavailable`.
+#[derive(Debug, Clone)]
+pub struct GitOptions {
+    /// How many processes may run at once.
+    pub processes: NonZeroUsize,
+    /// How long a process may run before it's killed.
+    pub timeout: Duration,
+    /// How long a process may go without output before it's killed.
+    pub idle_timeout: Duration,
+}
+
+impl Default for GitOptions {
+    fn default() -> Self {
+        Self {
+            processes: DEFAULT_GIT_PROCESSES,
+            timeout: DEFAULT_GIT_TIMEOUT,
+            idle_timeout: DEFAULT_GIT_IDLE_TIMEOUT,
+        }
+    }
+}
+
+/// Per-client request rate limits. Each router has its own quota, and
+/// `None` leaves it unlimited.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitOptions {
+    /// Quota for `/api` and `/badges`.
+    pub api: Option<Quota>,
+    /// Quota for `/raw`.
+    pub raw: Option<Quota>,
+    /// Quota for git fetches and clones.
+    pub git: Option<Quota>,
+    /// Proxies whose `X-Forwarded-For` headers name the client.
+    pub trusted_proxies: Vec<TrustedProxy>,
 }
 
 #[derive(Debug, Clone)]
@@ -103,6 +202,18 @@ pub async fn run(options: Options) -> anyhow::Result<()> {
     let profile = Arc::new(profile);
     let ctx = api::Context::new(profile.clone(), web_config.clone(), &options)?;
 
+    tokio::spawn(ctx.events().clone().listen(profile.clone()));
+    if let Some(cache) = ctx.cache() {
+        tokio::spawn(cache.clone().follow(ctx.events().clone()));
+    }
+    tokio::spawn(ctx.webhooks().clone().run(ctx.clone()));
+    tokio::spawn(ctx.summaries().clone().run(ctx.clone()));
+    if let Some(bundles) = ctx.bundles() {
+        tokio::spawn(bundles.clone().run(profile.clone(), ctx.events().clone()));
+    }
+
+    #[cfg(unix)]
+    let webhooks = ctx.webhooks().clone();
     #[cfg(unix)]
     tokio::spawn(async move {
         let mut sighup = signal(SignalKind::hangup()).expect("Failed to register SIGHUP handler");
@@ -118,6 +229,7 @@ pub async fn run(options: Options) -> anyhow::Result<()> {
                             *config = new_profile.config.web.clone();
                         })
                         .await;
+                    webhooks.set_hooks(api::webhooks::load(&new_profile)).await;
                     tracing::info!("Web configuration reloaded successfully");
                 }
                 Err(e) => {
@@ -178,22 +290,49 @@ pub async fn run(options: Options) -> anyhow::Result<()> {
 
 /// Create a router consisting of other sub-routers.
 fn router(options: Options, profile: Arc<Profile>, ctx: api::Context) -> anyhow::Result<Router> {
+    let badges_router = api::badges::router(ctx.clone());
+    let bundles = ctx.bundles().cloned();
     let api_router = api::router(ctx);
+    let methods = if options.write {
+        vec![
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ]
+    } else {
+        vec![Method::GET]
+    };
     let aliases = Arc::new(options.aliases);
-    let git_router = git::router(profile.clone(), aliases.clone());
-    let raw_router = raw::router(profile, aliases);
+    let git_router = git::router(profile.clone(), aliases.clone(), &options.git, bundles);
+    let max_blob_size = options.max_blob_size.get().saturating_mul(1024 * 1024);
+    let raw_router = raw::router(profile, aliases, max_blob_size);
+
+    let limits = options.rate_limit;
+    let proxies = Arc::new(limits.trusted_proxies);
+    let limiter = |scope, quota: Option<Quota>| {
+        quota.map(|quota| Limiter::new(scope, quota, proxies.clone()))
+    };
+    // The API and badges share a quota.
+    let api_limiter = limiter(Scope::Api, limits.api);
+    let api_router = rate_limit::layer(api_router, api_limiter.clone());
+    let badges_router = rate_limit::layer(badges_router, api_limiter);
+    let git_router = rate_limit::layer(git_router, limiter(Scope::Git, limits.git));
+    let raw_router = rate_limit::layer(raw_router, limiter(Scope::Raw, limits.raw));
 
     let app = Router::new()
         .route("/", get(root_index_handler))
         .merge(git_router)
         .nest("/api", api_router)
         .nest("/raw", raw_router)
+        .nest("/badges", badges_router)
         .layer(
             CorsLayer::new()
                 .max_age(Duration::from_secs(86400))
                 .allow_origin(cors::Any)
-                .allow_methods([Method::GET])
-                .allow_headers([CONTENT_TYPE]),
+                .allow_methods(methods)
+                .allow_headers([CONTENT_TYPE, AUTHORIZATION]),
         );
 
     Ok(app)
@@ -225,6 +364,11 @@ async fn root_index_handler() -> impl IntoResponse {
                 "rel": "file_by_oid",
                 "type": "GET"
             },
+            {
+                "href": "/badges/:rid/:badge",
+                "rel": "badge",
+                "type": "GET"
+            },
             {
                 "href": "/:rid/*request",
                 "rel": "git",
@@ -284,7 +428,14 @@ mod routes {
             aliases: HashMap::new(),
             listen: DualAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], 8080))),
             cache: None,
+            cache_dir: None,
             search: None,
+            write: false,
+            blocking: super::BlockingOptions::default(),
+            rate_limit: super::RateLimitOptions::default(),
+            git: super::GitOptions::default(),
+            bundle_dir: None,
+            max_blob_size: super::DEFAULT_MAX_BLOB_SIZE,
         };
         let profile = test::profile(tmp.path(), [0xff; 32]);
         let web_config = crate::api::WebConfig::from_profile(&profile);
@@ -301,4 +452,52 @@ mod routes {
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    #[tokio::test]
+    async fn test_rate_limited_returns_429() {
+        let tmp = tempfile::tempdir().unwrap();
+        let options = super::Options {
+            aliases: HashMap::new(),
+            listen: DualAddr::Tcp(SocketAddr::from(([0, 0, 0, 0], 8080))),
+            cache: None,
+            cache_dir: None,
+            search: None,
+            write: false,
+            blocking: super::BlockingOptions::default(),
+            rate_limit: super::RateLimitOptions {
+                api: Some("60:1".parse().unwrap()),
+                trusted_proxies: vec!["10.0.0.0/8".parse().unwrap()],
+                ..super::RateLimitOptions::default()
+            },
+            git: super::GitOptions::default(),
+            bundle_dir: None,
+            max_blob_size: super::DEFAULT_MAX_BLOB_SIZE,
+        };
+        let profile = test::profile(tmp.path(), [0xff; 32]);
+        let web_config = crate::api::WebConfig::from_profile(&profile);
+        let profile = std::sync::Arc::new(profile);
+        let ctx = crate::api::Context::new(profile.clone(), web_config, &options).unwrap();
+        let app = super::router(options, profile, ctx)
+            .unwrap()
+            .layer(MockConnectInfo(DualAddr::Tcp(SocketAddr::from((
+                [10, 0, 0, 1],
+                8080,
+            )))));
+
+        let response = test::get(&app, "/api").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["ratelimit-remaining"], "0");
+
+        let response = test::get(&app, "/api/v1").await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "1");
+        assert_eq!(response.json().await["code"], 429);
+
+        // Clients behind the proxy have their own buckets, and raw isn't limited.
+        let response =
+            test::get_with_headers(&app, "/api", &[("X-Forwarded-For", "192.0.2.1")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = test::get(&app, "/raw/aa").await;
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
 }
@@ -1,4 +1,5 @@
 use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::{collections::HashMap, process};
 
 use anyhow::bail;
@@ -27,7 +28,29 @@ Options
     --alias, -a    <alias> <rid>     Provide alias and RID pairs to use in place of the RID for a repository,
                                      e.g. heartwood and rad:z3gqcJUoA1n9HaHKufZs5FCSGazv5 to produce https://seed.radicle.dev/heartwood.git
                                      Aliases work anywhere the RID is accepted: git clone, the JSON API and raw endpoints.
-    --cache        <number>          Max amount of items in cache for /tree endpoints (default: 100)
+    --cache        <mib>             Response cache budget in MiB for commit, diff, tree, blob, readme and stats endpoints (default: 100)
+    --cache-dir    <path>            Also keep immutable responses in this directory, so they survive restarts
+    --cache-dir-size <mib>           Budget of the cache directory in MiB (default: 1024)
+    --blocking-limit <n>             Repository reads running at once, for each of the cheap and expensive
+                                     API routes (default: 32)
+    --blocking-queue <n>             Repository reads waiting for a slot before requests are answered with
+                                     503 Service Unavailable (default: 256)
+    --rate-limit-api <n>[:<burst>]   Limit each client to <n> requests per minute to the JSON API and badges,
+                                     in bursts of up to <burst> (default: unlimited)
+    --rate-limit-raw <n>[:<burst>]   Likewise, for raw files and archives (default: unlimited)
+    --rate-limit-git <n>[:<burst>]   Likewise, for git fetches and clones (default: unlimited)
+    --trusted-proxy <addr>[/<len>]   Honor X-Forwarded-For from this proxy address or network when
+                                     identifying clients. May be given multiple times. Clients connecting
+                                     over a Unix socket are always treated as proxies.
+    --git-processes <n>              Git fetches and clones served at once (default: 32)
+    --git-timeout <secs>             Abort git fetches and clones running longer than this (default: 600)
+    --git-idle-timeout <secs>        Abort git fetches and clones stalled for this long (default: 60)
+    --bundle-dir   <path>            Build clone bundles of public repos from their canonical refs in this
+                                     directory, and offer them to git clients via bundle-uri
+    --max-blob-size <mib>            Largest file served raw, in MiB (default: 1024)
+    --write                          Enable the authenticated write API for commenting on, reacting to,
+                                     labeling and closing issues and patches. Sessions can only be opened
+                                     by the node's own identity, whose key signs the resulting changes.
     --version, -v                    Print program version
     --help, -h                       Print help
 
@@ -35,6 +58,11 @@ Environment
 
     RUST_LOG                         Set the log level (e.g. RUST_LOG=warn, or
                                      RUST_LOG=radicle_httpd=debug). Defaults to "info".
+
+Requirements
+
+    git                              Serves clones, fetches, raw files and archives
+    xz, zstd                         Compress .tar.xz and .tar.zst archives
 "#;
 
 #[tokio::main]
@@ -64,6 +92,14 @@ fn parse_options() -> anyhow::Result<httpd::Options> {
     let mut listen = None;
     let mut aliases = HashMap::new();
     let mut cache = Some(httpd::DEFAULT_CACHE_SIZE);
+    let mut cache_dir = None;
+    let mut cache_dir_size = httpd::DEFAULT_CACHE_DIR_SIZE;
+    let mut write = false;
+    let mut blocking = httpd::BlockingOptions::default();
+    let mut rate_limit = httpd::RateLimitOptions::default();
+    let mut git = httpd::GitOptions::default();
+    let mut bundle_dir = None;
+    let mut max_blob_size = httpd::DEFAULT_MAX_BLOB_SIZE;
 
     while let Some(arg) = parser.next()? {
         match arg {
@@ -105,6 +141,49 @@ fn parse_options() -> anyhow::Result<httpd::Options> {
                 let size = parser.value()?.parse()?;
                 cache = NonZeroUsize::new(size);
             }
+            Long("cache-dir") => {
+                cache_dir = Some(PathBuf::from(parser.value()?));
+            }
+            Long("cache-dir-size") => {
+                cache_dir_size = parser.value()?.parse()?;
+            }
+            Long("blocking-limit") => {
+                blocking.limit = parser.value()?.parse()?;
+            }
+            Long("blocking-queue") => {
+                blocking.queue = parser.value()?.parse()?;
+            }
+            Long("rate-limit-api") => {
+                rate_limit.api = Some(parser.value()?.parse()?);
+            }
+            Long("rate-limit-raw") => {
+                rate_limit.raw = Some(parser.value()?.parse()?);
+            }
+            Long("rate-limit-git") => {
+                rate_limit.git = Some(parser.value()?.parse()?);
+            }
+            Long("trusted-proxy") => {
+                let proxy = parser.value()?.parse()?;
+                rate_limit.trusted_proxies.push(proxy);
+            }
+            Long("git-processes") => {
+                git.processes = parser.value()?.parse()?;
+            }
+            Long("git-timeout") => {
+                git.timeout = std::time::Duration::from_secs(parser.value()?.parse()?);
+            }
+            Long("git-idle-timeout") => {
+                git.idle_timeout = std::time::Duration::from_secs(parser.value()?.parse()?);
+            }
+            Long("bundle-dir") => {
+                bundle_dir = Some(PathBuf::from(parser.value()?));
+            }
+            Long("max-blob-size") => {
+                max_blob_size = parser.value()?.parse()?;
+            }
+            Long("write") => {
+                write = true;
+            }
             Long("help") | Short('h') => {
                 println!("{HELP_MSG}");
                 process::exit(0);
@@ -116,7 +195,17 @@ fn parse_options() -> anyhow::Result<httpd::Options> {
         aliases,
         listen: listen.unwrap_or_else(|| DualAddr::Tcp(([0, 0, 0, 0], 8080).into())),
         cache,
+        cache_dir: cache_dir.map(|path| httpd::CacheDirOptions {
+            path,
+            size: cache_dir_size,
+        }),
         search: search_options_from_env()?,
+        write,
+        blocking,
+        rate_limit,
+        git,
+        bundle_dir,
+        max_blob_size,
     })
 }
 
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::net::{IpAddr, Ipv6Addr};
+use std::num::NonZeroU32;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router};
+use axum_listener::DualAddr;
+use serde_json::json;
+use tokio::time::Instant;
+
+/// How often idle buckets are dropped.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+const RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("ratelimit-limit");
+const RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("ratelimit-remaining");
+const RATELIMIT_RESET: HeaderName = HeaderName::from_static("ratelimit-reset");
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// How many requests a client may make: `per_minute` on average, in bursts
+/// of up to `burst`. Written as `<per-minute>[:<burst>]`, where the burst
+/// defaults to the per-minute rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub per_minute: NonZeroU32,
+    pub burst: NonZeroU32,
+}
+
+impl Quota {
+    /// Tokens added to a bucket per second.
+    fn refill(&self) -> f64 {
+        f64::from(self.per_minute.get()) / 60.
+    }
+}
+
+impl FromStr for Quota {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (per_minute, burst) = match s.split_once(':') {
+            Some((per_minute, burst)) => (per_minute, Some(burst)),
+            None => (s, None),
+        };
+        let per_minute = per_minute
+            .parse::<NonZeroU32>()
+            .map_err(|_| format!("invalid requests per minute {per_minute:?}"))?;
+        let burst = match burst {
+            Some(burst) => burst
+                .parse::<NonZeroU32>()
+                .map_err(|_| format!("invalid burst {burst:?}"))?,
+            None => per_minute,
+        };
+
+        Ok(Self { per_minute, burst })
+    }
+}
+
+/// An address or network of proxies whose `X-Forwarded-For` headers are
+/// honored, written like `10.0.0.1` or `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedProxy {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl TrustedProxy {
+    fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let (net, ip) = (u128::from(u32::from(net)), u128::from(u32::from(ip)));
+                mask(net, self.prefix, 32) == mask(ip, self.prefix, 32)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                mask(u128::from(net), self.prefix, 128) == mask(u128::from(ip), self.prefix, 128)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for TrustedProxy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| format!("invalid address {addr:?}"))?
+            .to_canonical();
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or_else(|| format!("invalid prefix length {prefix:?}"))?,
+            None => max,
+        };
+
+        Ok(Self { addr, prefix })
+    }
+}
+
+impl fmt::Display for TrustedProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Keep the first `prefix` bits of an address of `bits` bits.
+fn mask(n: u128, prefix: u8, bits: u8) -> u128 {
+    if prefix == 0 {
+        return 0;
+    }
+    n & (u128::MAX << (bits - prefix))
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn full(quota: &Quota, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(quota.burst.get()),
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, quota: &Quota, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+
+        self.tokens = (self.tokens + elapsed * quota.refill()).min(f64::from(quota.burst.get()));
+        self.updated = now;
+    }
+}
+
+/// The outcome of taking a token, as reported in response headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Decision {
+    allowed: bool,
+    limit: u32,
+    remaining: u32,
+    /// Seconds until the bucket is full again.
+    reset: u64,
+    /// Seconds until the next request is allowed, if this one isn't.
+    retry_after: u64,
+}
+
+/// The routes a set of buckets applies to. Each router has its own buckets,
+/// so that cloning a repo doesn't use up a client's API quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Api,
+    Raw,
+    Git,
+}
+
+/// Token-bucket rate limiting of clients, by address.
+#[derive(Clone)]
+pub struct Limiter {
+    scope: Scope,
+    quota: Quota,
+    proxies: Arc<Vec<TrustedProxy>>,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+#[derive(Debug)]
+struct Buckets {
+    by_client: HashMap<IpAddr, Bucket>,
+    /// When idle buckets were last dropped.
+    pruned: Instant,
+}
+
+impl Limiter {
+    pub fn new(scope: Scope, quota: Quota, proxies: Arc<Vec<TrustedProxy>>) -> Self {
+        Self {
+            scope,
+            quota,
+            proxies,
+            buckets: Arc::new(Mutex::new(Buckets {
+                by_client: HashMap::new(),
+                pruned: Instant::now(),
+            })),
+        }
+    }
+
+    /// Take a token from `client`'s bucket.
+    fn check(&self, client: IpAddr, now: Instant) -> Decision {
+        let quota = &self.quota;
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if now.duration_since(buckets.pruned) >= PRUNE_INTERVAL {
+            // A bucket that refilled completely is no different from a new one.
+            buckets.by_client.retain(|_, bucket| {
+                bucket.refill(quota, now);
+                bucket.tokens < f64::from(quota.burst.get())
+            });
+            buckets.pruned = now;
+        }
+        let bucket = buckets
+            .by_client
+            .entry(client)
+            .or_insert_with(|| Bucket::full(quota, now));
+
+        bucket.refill(quota, now);
+
+        let allowed = bucket.tokens >= 1.;
+        if allowed {
+            bucket.tokens -= 1.;
+        }
+        let missing = f64::from(quota.burst.get()) - bucket.tokens;
+
+        Decision {
+            allowed,
+            limit: quota.burst.get(),
+            remaining: bucket.tokens as u32,
+            reset: (missing / quota.refill()).ceil() as u64,
+            retry_after: ((1. - bucket.tokens).max(0.) / quota.refill()).ceil() as u64,
+        }
+    }
+
+    /// The address a request is counted against. Requests from trusted
+    /// proxies, and over the Unix socket, are counted against the last
+    /// untrusted address they were forwarded for.
+    fn client(&self, peer: Option<&DualAddr>, headers: &HeaderMap) -> IpAddr {
+        let peer = match peer {
+            Some(DualAddr::Tcp(addr)) => Some(addr.ip().to_canonical()),
+            #[cfg(unix)]
+            Some(DualAddr::Uds(_)) => None,
+            None => None,
+        };
+        if let Some(ip) = peer.filter(|ip| !self.is_trusted(ip)) {
+            return bucket_key(ip);
+        }
+        let forwarded = headers
+            .get_all(X_FORWARDED_FOR)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|hop| hop.trim().parse::<IpAddr>().map(|ip| ip.to_canonical()))
+            .collect::<Vec<_>>();
+        // Only the hops appended by trusted proxies can be believed, so walk
+        // back from the nearest one.
+        let mut client = peer;
+        for hop in forwarded.into_iter().rev() {
+            let Ok(ip) = hop else {
+                break;
+            };
+            client = Some(ip);
+            if !self.is_trusted(&ip) {
+                break;
+            }
+        }
+        bucket_key(client.unwrap_or(IpAddr::from(Ipv6Addr::LOCALHOST)))
+    }
+
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.proxies.iter().any(|proxy| proxy.contains(ip))
+    }
+}
+
+/// Apply `limiter` to every route of `router`, if there's one.
+pub fn layer(router: Router, limiter: Option<Limiter>) -> Router {
+    match limiter {
+        Some(limiter) => router.layer(middleware::from_fn_with_state(limiter, limit)),
+        None => router,
+    }
+}
+
+/// IPv6 clients usually get a whole `/64`, so they're counted by network.
+fn bucket_key(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from(mask(u128::from(ip), 64, 128))),
+    }
+}
+
+async fn limit(State(limiter): State<Limiter>, request: Request, next: Next) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<DualAddr>>()
+        .map(|ConnectInfo(addr)| addr);
+    let client = limiter.client(peer, request.headers());
+    let decision = limiter.check(client, Instant::now());
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        tracing::debug!("Rate limiting {client} on {:?} routes", limiter.scope);
+
+        let status = StatusCode::TOO_MANY_REQUESTS;
+        let mut response = match limiter.scope {
+            // Matches the API's error responses.
+            Scope::Api => (
+                status,
+                Json(json!({
+                    "error": status.canonical_reason(),
+                    "code": status.as_u16(),
+                })),
+            )
+                .into_response(),
+            Scope::Raw | Scope::Git => status.into_response(),
+        };
+        response.headers_mut().insert(
+            axum::http::header::RETRY_AFTER,
+            HeaderValue::from(decision.retry_after),
+        );
+        response
+    };
+    let headers = response.headers_mut();
+    headers.insert(RATELIMIT_LIMIT, HeaderValue::from(decision.limit));
+    headers.insert(RATELIMIT_REMAINING, HeaderValue::from(decision.remaining));
+    headers.insert(RATELIMIT_RESET, HeaderValue::from(decision.reset));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+
+    fn limiter(quota: &str, proxies: &[&str]) -> Limiter {
+        Limiter::new(
+            Scope::Api,
+            quota.parse().unwrap(),
+            Arc::new(proxies.iter().map(|p| p.parse().unwrap()).collect()),
+        )
+    }
+
+    fn forwarded(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_FORWARDED_FOR, value.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn quotas_parse() {
+        let quota = "120:10".parse::<Quota>().unwrap();
+        assert_eq!((quota.per_minute.get(), quota.burst.get()), (120, 10));
+
+        let quota = "60".parse::<Quota>().unwrap();
+        assert_eq!((quota.per_minute.get(), quota.burst.get()), (60, 60));
+
+        assert!("0".parse::<Quota>().is_err());
+        assert!("60:".parse::<Quota>().is_err());
+    }
+
+    #[test]
+    fn trusted_proxies_match_networks() {
+        let net = "10.1.0.0/16".parse::<TrustedProxy>().unwrap();
+        assert!(net.contains(&"10.1.2.3".parse().unwrap()));
+        assert!(net.contains(&"::ffff:10.1.2.3".parse().unwrap()));
+        assert!(!net.contains(&"10.2.0.1".parse().unwrap()));
+
+        let host = "::1".parse::<TrustedProxy>().unwrap();
+        assert!(host.contains(&"::1".parse().unwrap()));
+        assert!(!host.contains(&"::2".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<TrustedProxy>().is_err());
+        assert!("0.0.0.0/0"
+            .parse::<TrustedProxy>()
+            .unwrap()
+            .contains(&"1.2.3.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let limiter = limiter("60:2", &[]);
+        let client = "1.2.3.4".parse().unwrap();
+        let now = Instant::now();
+
+        assert!(limiter.check(client, now).allowed);
+        assert!(limiter.check(client, now).allowed);
+
+        let denied = limiter.check(client, now);
+        assert!(!denied.allowed);
+        assert_eq!(denied.remaining, 0);
+        assert_eq!(denied.retry_after, 1);
+        assert_eq!(denied.reset, 2);
+
+        // Other clients have their own bucket.
+        assert!(limiter.check("1.2.3.5".parse().unwrap(), now).allowed);
+        assert!(limiter.check(client, now + Duration::from_secs(1)).allowed);
+    }
+
+    #[test]
+    fn forwarded_for_is_only_honored_from_trusted_proxies() {
+        let limiter = limiter("60", &["10.0.0.0/8"]);
+        let proxy = DualAddr::Tcp(SocketAddr::from(([10, 0, 0, 1], 443)));
+        let stranger = DualAddr::Tcp(SocketAddr::from(([1, 1, 1, 1], 443)));
+
+        assert_eq!(
+            limiter.client(Some(&stranger), &forwarded("2.2.2.2")),
+            "1.1.1.1".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            limiter.client(Some(&proxy), &forwarded("2.2.2.2")),
+            "2.2.2.2".parse::<IpAddr>().unwrap()
+        );
+        // A client can prepend anything, only the hops added by our proxies
+        // are believed.
+        assert_eq!(
+            limiter.client(Some(&proxy), &forwarded("3.3.3.3, 2.2.2.2, 10.0.0.2")),
+            "2.2.2.2".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            limiter.client(Some(&proxy), &HeaderMap::new()),
+            "10.0.0.1".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            limiter.client(Some(&proxy), &forwarded("2001:db8::1")),
+            "2001:db8::".parse::<IpAddr>().unwrap()
+        );
+    }
+}
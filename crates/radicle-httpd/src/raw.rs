@@ -8,24 +8,28 @@ use axum::http::{header, HeaderValue, Method, Response, StatusCode};
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::Router;
+use futures_util::{future, stream, StreamExt as _, TryStreamExt as _};
 use hyper::HeaderMap;
-use radicle_surf::blob::{Blob, BlobRef};
 
-use radicle::git::raw::ErrorCode;
+use radicle::cob::issue::cache::Issues as _;
+use radicle::cob::patch::cache::Patches as _;
+use radicle::cob::{thread, Embed, ObjectId, Uri};
+use radicle::git::raw::{ErrorCode, ObjectType};
 use radicle::git::Oid;
 use radicle::prelude::RepoId;
 use radicle::profile::Profile;
-use radicle::storage::{ReadRepository, ReadStorage};
-use radicle_surf::Repository;
-use tokio::io::BufReader;
+use radicle::storage::{self, ReadRepository, ReadStorage};
+use tokio::io::{AsyncReadExt as _, BufReader};
 use tokio::process::Command;
 use tokio_util::io::ReaderStream;
 
 use crate::api::query::RawQuery;
-use crate::axum_extra::Path;
+use crate::axum_extra::{ETag, Path};
 use crate::error::RawError as Error;
 
-const MAX_BLOB_SIZE: usize = 10_485_760;
+/// Blobs up to this size are read into memory. Larger ones are streamed from
+/// `git cat-file`.
+const BUFFER_LIMIT: usize = 10_485_760;
 
 /// Values for `git archive --format` that we support.
 #[derive(Debug, Default)]
@@ -38,6 +42,8 @@ enum ArchiveFormat {
     // also surprise less.
     #[default]
     TarGz,
+    TarXz,
+    TarZst,
     Zip,
 }
 
@@ -46,6 +52,8 @@ impl ArchiveFormat {
         match self {
             ArchiveFormat::Tar => "tar",
             ArchiveFormat::TarGz => "tar.gz",
+            ArchiveFormat::TarXz => "tar.xz",
+            ArchiveFormat::TarZst => "tar.zst",
             ArchiveFormat::Zip => "zip",
         }
     }
@@ -54,6 +62,8 @@ impl ArchiveFormat {
         match self {
             ArchiveFormat::Tar => ".tar",
             ArchiveFormat::TarGz => ".tar.gz",
+            ArchiveFormat::TarXz => ".tar.xz",
+            ArchiveFormat::TarZst => ".tar.zst",
             ArchiveFormat::Zip => ".zip",
         }
     }
@@ -64,10 +74,24 @@ impl ArchiveFormat {
         match self {
             ArchiveFormat::Tar => "application/x-tar",
             ArchiveFormat::TarGz => "application/gzip",
+            ArchiveFormat::TarXz => "application/x-xz",
+            ArchiveFormat::TarZst => "application/zstd",
             ArchiveFormat::Zip => "application/zip",
         }
     }
 
+    /// The command compressing `git archive`'s tar output, for formats `git`
+    /// doesn't support natively. These need `xz` and `zstd` to be installed.
+    /// Compression is single-threaded, because the output of multithreaded
+    /// compressors may depend on the thread count.
+    const fn filter(&self) -> Option<&'static str> {
+        match self {
+            ArchiveFormat::TarXz => Some("xz -c -T1"),
+            ArchiveFormat::TarZst => Some("zstd -c -q -T1"),
+            ArchiveFormat::Tar | ArchiveFormat::TarGz | ArchiveFormat::Zip => None,
+        }
+    }
+
     /// Detect the archive format from the suffix of the given string.
     /// If a supported suffix is found, the suffix is stripped from the string
     /// and the corresponding format is returned.
@@ -78,6 +102,10 @@ impl ArchiveFormat {
             (stripped, Some(ArchiveFormat::Tar))
         } else if let Some(stripped) = s.strip_suffix(ArchiveFormat::TarGz.extension()) {
             (stripped, Some(ArchiveFormat::TarGz))
+        } else if let Some(stripped) = s.strip_suffix(ArchiveFormat::TarXz.extension()) {
+            (stripped, Some(ArchiveFormat::TarXz))
+        } else if let Some(stripped) = s.strip_suffix(ArchiveFormat::TarZst.extension()) {
+            (stripped, Some(ArchiveFormat::TarZst))
         } else if let Some(stripped) = s.strip_suffix(ArchiveFormat::Zip.extension()) {
             (stripped, Some(ArchiveFormat::Zip))
         } else {
@@ -105,14 +133,34 @@ enum Committish<'a> {
     Ref(&'a str),
 }
 
-pub fn router(profile: Arc<Profile>, aliases: Arc<HashMap<String, RepoId>>) -> Router {
+/// Shared state of the raw routes.
+#[derive(Clone)]
+struct Raw {
+    profile: Arc<Profile>,
+    aliases: Arc<HashMap<String, RepoId>>,
+    /// Size of the largest blob served, in bytes.
+    max_blob_size: usize,
+}
+
+pub fn router(
+    profile: Arc<Profile>,
+    aliases: Arc<HashMap<String, RepoId>>,
+    max_blob_size: usize,
+) -> Router {
+    let raw = Raw {
+        profile,
+        aliases,
+        max_blob_size,
+    };
+
     Router::new()
         .route("/{rid}/{sha}", get(commit_handler))
         .route("/{rid}/{sha}/{*path}", get(file_by_commit_handler))
         .route("/{rid}/head/{*path}", get(file_by_canonical_head_handler))
         .route("/{rid}/archive/{*refname}", get(archive_by_refname_handler))
         .route("/{rid}/blobs/{oid}", get(file_by_oid_handler))
-        .with_state((profile, aliases))
+        .route("/{rid}/embeds/{cob}/{oid}", get(embed_handler))
+        .with_state(raw)
 }
 
 /// Resolve a repo path segment to a [`RepoId`], mapping an unknown segment to
@@ -121,11 +169,23 @@ fn resolve_rid(repo: &str, aliases: &HashMap<String, RepoId>) -> Result<RepoId,
     crate::resolve_rid(repo, aliases).ok_or(Error::NotFound)
 }
 
+/// Map a git error to [`Error::NotFound`] if it's about a missing object or
+/// path.
+fn not_found(e: radicle::git::raw::Error) -> Error {
+    if e.code() == ErrorCode::NotFound {
+        Error::NotFound
+    } else {
+        Error::Git(e)
+    }
+}
+
 async fn commit_handler(
     method: Method,
     Path((rid, sha)): Path<(String, String)>,
     Query(q): Query<PrefixQuery>,
-    State((profile, aliases)): State<(Arc<Profile>, Arc<HashMap<String, RepoId>>)>,
+    State(Raw {
+        profile, aliases, ..
+    }): State<Raw>,
 ) -> Result<Response<Body>, Error> {
     let rid = resolve_rid(&rid, &aliases)?;
     let storage = &profile.storage;
@@ -144,12 +204,26 @@ async fn commit_handler(
         return Err(Error::BadRequest);
     };
 
-    archive_by_committish(method, rid, Committish::Oid(oid), q.prefix, format, profile).await
+    archive_by_committish(
+        method,
+        rid,
+        Committish::Oid(oid),
+        None,
+        q.prefix,
+        format,
+        profile,
+    )
+    .await
 }
 
 async fn file_by_commit_handler(
     Path((rid, sha, path)): Path<(String, Oid, String)>,
-    State((profile, aliases)): State<(Arc<Profile>, Arc<HashMap<String, RepoId>>)>,
+    State(Raw {
+        profile,
+        aliases,
+        max_blob_size,
+    }): State<Raw>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = resolve_rid(&rid, &aliases)?;
     let storage = &profile.storage;
@@ -160,27 +234,29 @@ async fn file_by_commit_handler(
         return Err(Error::NotFound);
     }
 
-    let repo: Repository = repo.backend.into();
-    let blob = repo.blob(
-        radicle_surf::Oid::from(radicle::git::raw::Oid::from(sha)),
-        &path,
-    )?;
+    let oid = blob_at(&repo, sha, &path)?;
 
-    blob_response(blob, &path)
+    blob_response(&repo, oid, Some(&path), max_blob_size, &headers)
 }
 
 async fn archive_by_refname_handler(
     method: Method,
     Path((rid, refname)): Path<(String, String)>,
     Query(q): Query<PrefixQuery>,
-    State((profile, aliases)): State<(Arc<Profile>, Arc<HashMap<String, RepoId>>)>,
+    State(Raw {
+        profile, aliases, ..
+    }): State<Raw>,
 ) -> Result<Response<Body>, Error> {
     let rid = resolve_rid(&rid, &aliases)?;
     let (refname, format) = ArchiveFormat::detect(&refname);
+    let repo = profile.storage.repository(rid)?;
+    let (refname, path) = split_refname(&repo, refname);
+
     archive_by_committish(
         method,
         rid,
         Committish::Ref(refname),
+        path,
         q.prefix,
         format.unwrap_or_default(),
         profile,
@@ -188,10 +264,38 @@ async fn archive_by_refname_handler(
     .await
 }
 
+/// Split the `{ref}/{*path}` of an archive route into the ref and the path,
+/// if there is one. Refnames may contain slashes too, so the ref is the
+/// longest prefix that names one.
+fn split_refname<'a>(repo: &storage::git::Repository, s: &'a str) -> (&'a str, Option<&'a str>) {
+    let mut refname = s;
+    loop {
+        if repo
+            .backend
+            .resolve_reference_from_short_name(refname)
+            .is_ok()
+        {
+            let path = s[refname.len()..].trim_matches('/');
+            return (refname, (!path.is_empty()).then_some(path));
+        }
+        match refname.rsplit_once('/') {
+            Some((prefix, _)) => refname = prefix,
+            // Not a ref, which is reported later on.
+            None => return (s, None),
+        }
+    }
+}
+
+/// Archive the tree of `committish`, or only the directory at `path` in it.
+///
+/// Archives of a directory keep its path, e.g. `<prefix>/crates/foo/..`,
+/// rather than starting at it: entries then get the commit's time, which
+/// keeps archives reproducible.
 async fn archive_by_committish(
     method: Method,
     rid: RepoId,
     committish: Committish<'_>,
+    path: Option<&str>,
     use_prefix: bool,
     format: ArchiveFormat,
     profile: Arc<Profile>,
@@ -225,12 +329,22 @@ async fn archive_by_committish(
             Error::Git(e)
         });
     }
+    if let Some(path) = path {
+        let tree = repo.backend.find_object(oid.into(), None)?.peel_to_tree()?;
+        let entry = tree
+            .get_path(std::path::Path::new(path))
+            .map_err(not_found)?;
+        if entry.kind() != Some(ObjectType::Tree) {
+            return Err(Error::NotFound);
+        }
+    }
 
     // Build a prefix for the archive, which includes the
-    // refname (if one was given):
+    // refname (if one was given) and the path (if one was given):
     //
     // Without refname:   <repo-name>-<oid>
     // With    refname:   <repo-name>-<refname>
+    // With    path:      <repo-name>-<refname>-<path>
     let prefix = {
         let mut build = String::from(repo_name);
         build.push('-');
@@ -243,6 +357,10 @@ async fn archive_by_committish(
                 refname.replace("/", "-")
             }
         });
+        if let Some(path) = path {
+            build.push('-');
+            build.push_str(&path.replace("/", "-"));
+        }
 
         build
     };
@@ -265,6 +383,13 @@ async fn archive_by_committish(
 
     let mut command = Command::new("git");
 
+    // Paths are taken as is, not as patterns.
+    command.arg("--literal-pathspecs");
+    if let Some(filter) = format.filter() {
+        command
+            .arg("-c")
+            .arg(format!("tar.{}.command={filter}", format.as_str()));
+    }
     command
         .arg("archive")
         .arg(format!("--format={}", format.as_str()));
@@ -276,21 +401,43 @@ async fn archive_by_committish(
     let mut child = command
         .arg(oid.to_string())
         .arg("--")
+        .args(path)
         .current_dir(repo.path())
         .stdout(std::process::Stdio::piped())
+        .kill_on_drop(true)
         .spawn()?;
 
-    let mut response = response;
-    *response.body_mut() = Body::from_stream(ReaderStream::new(BufReader::new(
+    let stdout = ReaderStream::new(BufReader::new(
         child.stdout.take().expect("stdout was captured"),
-    )));
+    ));
+    // The status is only known once the archive is sent. A failure, e.g. of
+    // the compressor, aborts the response, so that it can't pass for a
+    // complete archive.
+    let status = stream::once(async move {
+        match child.wait().await {
+            Ok(status) if status.success() => None,
+            Ok(status) => Some(Err(std::io::Error::other(format!(
+                "git archive failed: {status}"
+            )))),
+            Err(e) => Some(Err(e)),
+        }
+    })
+    .filter_map(future::ready);
+
+    let mut response = response;
+    *response.body_mut() = Body::from_stream(stdout.chain(status));
 
     Ok(response)
 }
 
 async fn file_by_canonical_head_handler(
     Path((rid, path)): Path<(String, String)>,
-    State((profile, aliases)): State<(Arc<Profile>, Arc<HashMap<String, RepoId>>)>,
+    State(Raw {
+        profile,
+        aliases,
+        max_blob_size,
+    }): State<Raw>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = resolve_rid(&rid, &aliases)?;
     let storage = &profile.storage;
@@ -302,38 +449,113 @@ async fn file_by_canonical_head_handler(
     }
 
     let (_, sha) = repo.head()?;
-    let repo: Repository = repo.backend.into();
-    let blob = repo.blob(
-        radicle_surf::Oid::from(radicle::git::raw::Oid::from(sha)),
-        &path,
-    )?;
+    let oid = blob_at(&repo, sha, &path)?;
 
-    blob_response(blob, &path)
+    blob_response(&repo, oid, Some(&path), max_blob_size, &headers)
 }
 
+/// Find the blob at `path` in the tree of `commit`, without reading it.
+fn blob_at(repo: &storage::git::Repository, commit: Oid, path: &str) -> Result<Oid, Error> {
+    let tree = repo
+        .backend
+        .find_commit(commit.into())
+        .map_err(not_found)?
+        .tree()?;
+    let entry = tree
+        .get_path(std::path::Path::new(path))
+        .map_err(not_found)?;
+    if entry.kind() != Some(ObjectType::Blob) {
+        return Err(Error::NotFound);
+    }
+    Ok(entry.id().into())
+}
+
+/// Respond with the blob `oid`, named `name` if it's in a tree.
+///
+/// Blobs up to [`BUFFER_LIMIT`] are read into memory and, when the name
+/// doesn't tell, their MIME type is guessed from their content. Larger ones
+/// are streamed, and are `application/octet-stream` unless named otherwise.
 fn blob_response(
-    blob: Blob<BlobRef>,
-    path: &str,
-) -> Result<(StatusCode, HeaderMap, Vec<u8>), Error> {
-    let mut response_headers = HeaderMap::new();
-    if blob.size() > MAX_BLOB_SIZE {
-        return Ok::<_, Error>((StatusCode::PAYLOAD_TOO_LARGE, response_headers, vec![]));
+    repo: &storage::git::Repository,
+    oid: Oid,
+    name: Option<&str>,
+    max_blob_size: usize,
+    headers: &HeaderMap,
+) -> Result<Response<Body>, Error> {
+    let (size, kind) = repo
+        .backend
+        .odb()?
+        .read_header(oid.into())
+        .map_err(not_found)?;
+    if kind != ObjectType::Blob {
+        return Err(Error::NotFound);
+    }
+    if size > max_blob_size {
+        return Ok(StatusCode::PAYLOAD_TOO_LARGE.into_response());
     }
+    let etag = ETag::new(oid);
+    let guess = name.and_then(|name| mime_guess::from_path(name).first_raw());
 
-    let mime = mime_guess::from_path(path)
-        .first_raw()
-        .or_else(|| infer::get(blob.content()).map(|i| i.mime_type()))
+    if size > BUFFER_LIMIT {
+        let mime = guess.unwrap_or("application/octet-stream");
+        return content_response(etag, mime, size, headers, |range| {
+            cat_file(repo.path(), oid, range)
+        });
+    }
+
+    let blob = repo.backend.find_blob(oid.into())?;
+    let content = blob.content();
+    let mime = guess
+        .or_else(|| infer::get(content).map(|i| i.mime_type()))
         .unwrap_or("application/octet-stream");
 
-    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_str(mime)?);
+    content_response(etag, mime, size, headers, |range| {
+        Ok(Body::from(content[range].to_vec()))
+    })
+}
 
-    Ok::<_, Error>((StatusCode::OK, response_headers, blob.content().to_owned()))
+/// Stream the bytes in `range` of the blob `oid`, without reading the whole
+/// blob into memory.
+fn cat_file(
+    git_dir: &std::path::Path,
+    oid: Oid,
+    range: std::ops::Range<usize>,
+) -> Result<Body, Error> {
+    let mut child = Command::new("git")
+        .args(["cat-file", "blob"])
+        .arg(oid.to_string())
+        .current_dir(git_dir)
+        .stdout(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout was captured");
+    let (skip, len) = (range.start as u64, range.len() as u64);
+
+    let body = stream::once(async move {
+        let mut stdout = BufReader::new(stdout);
+        tokio::io::copy(&mut (&mut stdout).take(skip), &mut tokio::io::sink()).await?;
+        Ok::<_, std::io::Error>(ReaderStream::new(stdout.take(len)))
+    })
+    .try_flatten()
+    // The process is killed once the body is dropped, e.g. when the client
+    // goes away before the end.
+    .map(move |chunk| {
+        let _child = &child;
+        chunk
+    });
+
+    Ok(Body::from_stream(body))
 }
 
 async fn file_by_oid_handler(
     Path((rid, oid)): Path<(String, Oid)>,
-    State((profile, aliases)): State<(Arc<Profile>, Arc<HashMap<String, RepoId>>)>,
+    State(Raw {
+        profile,
+        aliases,
+        max_blob_size,
+    }): State<Raw>,
     Query(_qs): Query<RawQuery>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
     let rid = resolve_rid(&rid, &aliases)?;
     let storage = &profile.storage;
@@ -344,21 +566,205 @@ async fn file_by_oid_handler(
         return Err(Error::NotFound);
     }
 
-    let blob = repo.blob(oid)?;
-    let content = blob.content();
-    let mime = infer::get(content).map(|i| i.mime_type().to_string());
+    blob_response(&repo, oid, None, max_blob_size, &headers)
+}
+
+/// Respond with the content of a blob of `len` bytes, or with the part of it
+/// asked for by the request's `Range` header. `body` produces the bytes in a
+/// range. Blobs are content-addressed, so `etag` is made from the blob's id.
+fn content_response(
+    etag: ETag,
+    mime: &str,
+    len: usize,
+    headers: &HeaderMap,
+    body: impl FnOnce(std::ops::Range<usize>) -> Result<Body, Error>,
+) -> Result<Response<Body>, Error> {
+    if etag.matches(headers) {
+        return Ok(etag.not_modified());
+    }
+
     let mut response_headers = HeaderMap::new();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_str(mime)?);
+    response_headers.insert(header::ETAG, HeaderValue::from_str(etag.as_str())?);
+    response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+
+    // A range of a different version of the resource is useless, so the full
+    // content is sent instead. Only strong comparison is allowed here.
+    let if_range = headers
+        .get(header::IF_RANGE)
+        .map(|value| value.as_bytes() == etag.as_str().as_bytes())
+        .unwrap_or(true);
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|_| if_range)
+        .map(|value| ByteRange::parse(value, len))
+        .unwrap_or(ByteRange::Full);
+
+    let (status, range) = match range {
+        ByteRange::Full => (StatusCode::OK, 0..len),
+        ByteRange::Part(range) => {
+            response_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes {}-{}/{len}", range.start, range.end - 1))?,
+            );
+            (StatusCode::PARTIAL_CONTENT, range)
+        }
+        ByteRange::Unsatisfiable => {
+            response_headers.insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{len}"))?,
+            );
+            return Ok((StatusCode::RANGE_NOT_SATISFIABLE, response_headers).into_response());
+        }
+    };
+    // Streamed bodies don't know their length.
+    response_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(range.len()));
+
+    Ok((status, response_headers, body(range)?).into_response())
+}
+
+/// The part of a resource a `Range` header asks for, see
+/// <https://www.rfc-editor.org/rfc/rfc9110#name-range-requests>.
+#[derive(Debug, PartialEq, Eq)]
+enum ByteRange {
+    /// The whole resource. Malformed headers and requests for several
+    /// ranges, which we don't support, are answered with it too.
+    Full,
+    Part(std::ops::Range<usize>),
+    /// No byte of the range is within the resource.
+    Unsatisfiable,
+}
+
+impl ByteRange {
+    /// Parse a `Range` header value for a resource of `len` bytes.
+    fn parse(value: &str, len: usize) -> Self {
+        let Some(spec) = value.trim().strip_prefix("bytes=") else {
+            return Self::Full;
+        };
+        let Some((first, last)) = spec.trim().split_once('-') else {
+            return Self::Full;
+        };
+        if spec.contains(',') {
+            return Self::Full;
+        }
+        match (first.parse::<usize>(), last.parse::<usize>()) {
+            // The last `n` bytes.
+            (Err(_), Ok(n)) if first.is_empty() => {
+                if n == 0 || len == 0 {
+                    Self::Unsatisfiable
+                } else {
+                    Self::Part(len.saturating_sub(n)..len)
+                }
+            }
+            // Everything from `start`.
+            (Ok(start), Err(_)) if last.is_empty() => {
+                if start >= len {
+                    Self::Unsatisfiable
+                } else {
+                    Self::Part(start..len)
+                }
+            }
+            (Ok(start), Ok(end)) if start <= end => {
+                if start >= len {
+                    Self::Unsatisfiable
+                } else {
+                    Self::Part(start..end.min(len - 1) + 1)
+                }
+            }
+            _ => Self::Full,
+        }
+    }
+}
 
-    if blob.size() > MAX_BLOB_SIZE {
-        return Ok::<_, Error>((StatusCode::PAYLOAD_TOO_LARGE, response_headers, vec![]));
+/// Serve a file embedded in an issue or patch comment.
+/// `GET /raw/:rid/embeds/:cob/:oid`
+async fn embed_handler(
+    Path((rid, cob, oid)): Path<(String, Oid, Oid)>,
+    State(Raw {
+        profile,
+        aliases,
+        max_blob_size,
+    }): State<Raw>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let rid = resolve_rid(&rid, &aliases)?;
+    let storage = &profile.storage;
+    let repo = storage.repository(rid)?;
+
+    // Don't allow downloading embeds for private repos.
+    if repo.identity_doc()?.visibility().is_private() {
+        return Err(Error::NotFound);
     }
 
+    // Only serve blobs the COB actually embeds, so this can't be used to read
+    // arbitrary objects from the repository.
+    let name = embed_name(&profile, &repo, ObjectId::from(cob), &Uri::from(oid))?
+        .ok_or(Error::NotFound)?;
+    let mut response = blob_response(&repo, oid, Some(&name), max_blob_size, &headers)?;
+    let response_headers = response.headers_mut();
+
     response_headers.insert(
-        header::CONTENT_TYPE,
-        HeaderValue::from_str(&mime.unwrap_or("application/octet-stream".to_string()))?,
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    // Embeds are user uploads: don't let e.g. an SVG or HTML embed run
+    // scripts on this origin.
+    response_headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("sandbox"),
+    );
+    // Embeds are content-addressed and COB history is append-only, so a
+    // served embed never changes.
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("public, max-age=31536000, immutable"),
     );
 
-    Ok::<_, Error>((StatusCode::OK, response_headers, content.to_vec()))
+    Ok::<_, Error>(response)
+}
+
+/// Find the name under which the issue or patch `id` embeds `uri`, if it does.
+fn embed_name(
+    profile: &Profile,
+    repo: &radicle::storage::git::Repository,
+    id: ObjectId,
+    uri: &Uri,
+) -> Result<Option<String>, Error> {
+    fn find(embeds: &[Embed<Uri>], uri: &Uri) -> Option<String> {
+        embeds
+            .iter()
+            .find(|e| &e.content == uri)
+            .map(|e| e.name.clone())
+    }
+
+    fn find_in_comment<L>(comment: &thread::Comment<L>, uri: &Uri) -> Option<String> {
+        find(comment.embeds(), uri).or_else(|| comment.edits().find_map(|e| find(&e.embeds, uri)))
+    }
+
+    if let Some(issue) = profile.issues(repo)?.get(&id)? {
+        return Ok(issue.comments().find_map(|(_, c)| find_in_comment(c, uri)));
+    }
+    if let Some(patch) = profile.patches(repo)?.get(&id)? {
+        return Ok(patch.revisions().find_map(|(_, revision)| {
+            revision
+                .edits()
+                .find_map(|e| find(&e.embeds, uri))
+                .or_else(|| {
+                    revision
+                        .discussion()
+                        .comments()
+                        .find_map(|(_, c)| find_in_comment(c, uri))
+                })
+                .or_else(|| {
+                    revision.reviews().find_map(|(_, review)| {
+                        review.comments().find_map(|(_, c)| find_in_comment(c, uri))
+                    })
+                })
+        }));
+    }
+
+    Ok(None)
 }
 
 #[cfg(test)]
@@ -368,14 +774,20 @@ mod routes {
 
     use axum::http::StatusCode;
 
-    use crate::test::{self, get, RID, RID_PRIVATE};
+    use crate::test::{self, get, get_with_headers, HEAD, ISSUE_ID, RID, RID_PRIVATE};
+    use radicle::cob::issue::cache::Issues as _;
+    use radicle::cob::{Embed, Uri};
     use radicle::storage::ReadStorage;
 
     #[tokio::test]
     async fn test_file_handler() {
         let tmp = tempfile::tempdir().unwrap();
         let ctx = test::seed(tmp.path());
-        let app = super::router(ctx.profile().to_owned(), Arc::new(HashMap::new()));
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            usize::MAX,
+        );
 
         let response = get(&app, format!("/{RID}/head/dir1/README")).await;
 
@@ -400,7 +812,7 @@ mod routes {
             "hello".to_string(),
             RID.parse().unwrap(),
         )]));
-        let app = super::router(ctx.profile().to_owned(), aliases);
+        let app = super::router(ctx.profile().to_owned(), aliases, usize::MAX);
 
         // The alias serves the same content as the RID.
         let response = get(&app, "/hello/head/dir1/README").await;
@@ -411,4 +823,206 @@ mod routes {
         let response = get(&app, "/nope/head/dir1/README").await;
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    #[tokio::test]
+    async fn test_embed_handler() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let profile = ctx.profile().to_owned();
+        let app = super::router(profile.clone(), Arc::new(HashMap::new()), usize::MAX);
+        let content = b"\x89PNG\r\n\x1a\n";
+
+        let repo = profile.storage.repository(RID.parse().unwrap()).unwrap();
+        let oid = radicle::git::Oid::from(repo.backend.blob(content).unwrap());
+        let unreferenced = radicle::git::Oid::from(repo.backend.blob(b"secret").unwrap());
+        let signer = profile.signer().unwrap();
+        let mut issues = profile.issues_mut(&repo, &signer).unwrap();
+        let mut issue = issues.get_mut(&ISSUE_ID.parse().unwrap()).unwrap();
+        let root = *issue.root().0;
+        issue
+            .comment(
+                "See attached.",
+                root,
+                vec![Embed {
+                    name: "screenshot.png".to_owned(),
+                    content: Uri::from(oid),
+                }],
+            )
+            .unwrap();
+
+        let response = get(&app, format!("/{RID}/embeds/{ISSUE_ID}/{oid}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("Content-Type").unwrap(), "image/png");
+        assert_eq!(response.body().await, content.as_slice());
+
+        let response = get(&app, format!("/{RID}/embeds/{ISSUE_ID}/{unreferenced}")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = get(&app, format!("/{RID}/embeds/{HEAD}/{oid}")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_range_requests() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            usize::MAX,
+        );
+        let path = format!("/{RID}/{HEAD}/dir1/README");
+
+        let response = get(&app, &path).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["Accept-Ranges"], "bytes");
+        let etag = response.headers()["ETag"].to_str().unwrap().to_owned();
+
+        let response = get_with_headers(&app, &path, &[("Range", "bytes=6-10")]).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()["Content-Range"], "bytes 6-10/23");
+        assert_eq!(response.body().await, "World");
+
+        let response = get_with_headers(&app, &path, &[("Range", "bytes=-7")]).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body().await, "dir1!\n");
+
+        let response = get_with_headers(
+            &app,
+            &path,
+            &[("Range", "bytes=17-"), ("If-Range", etag.as_str())],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.body().await, "dir1!\n");
+
+        // The client's copy is outdated, so it gets the whole blob.
+        let response = get_with_headers(
+            &app,
+            &path,
+            &[("Range", "bytes=17-"), ("If-Range", "\"outdated\"")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body().await, "Hello World from dir1!\n");
+
+        let response = get_with_headers(&app, &path, &[("Range", "bytes=23-")]).await;
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(response.headers()["Content-Range"], "bytes */23");
+
+        let response = get_with_headers(&app, &path, &[("If-None-Match", etag.as_str())]).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        // Blobs by id are tagged the same way.
+        let oid = etag.trim_matches('"');
+        let response = get_with_headers(
+            &app,
+            format!("/{RID}/blobs/{oid}"),
+            &[("Range", "bytes=0-4")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()["ETag"], etag.as_str());
+        assert_eq!(response.body().await, "Hello");
+    }
+
+    #[test]
+    fn test_byte_range_parsing() {
+        use super::ByteRange;
+
+        assert_eq!(ByteRange::parse("bytes=0-0", 10), ByteRange::Part(0..1));
+        assert_eq!(ByteRange::parse("bytes=5-100", 10), ByteRange::Part(5..10));
+        assert_eq!(ByteRange::parse("bytes=-100", 10), ByteRange::Part(0..10));
+        assert_eq!(ByteRange::parse("bytes=-0", 10), ByteRange::Unsatisfiable);
+        assert_eq!(ByteRange::parse("bytes=0-1,4-5", 10), ByteRange::Full);
+        assert_eq!(ByteRange::parse("bytes=5-1", 10), ByteRange::Full);
+        assert_eq!(ByteRange::parse("items=0-1", 10), ByteRange::Full);
+    }
+
+    #[tokio::test]
+    async fn test_large_blobs_are_streamed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let profile = ctx.profile();
+        let rid = RID.parse().unwrap();
+        let repo = radicle::git::raw::Repository::open(radicle::storage::git::paths::repository(
+            &profile.storage,
+            &rid,
+        ))
+        .unwrap();
+        let content = (0..super::BUFFER_LIMIT + 1)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let oid = repo.blob(&content).unwrap();
+        let path = format!("/{RID}/blobs/{oid}");
+
+        let app = super::router(profile.to_owned(), Arc::new(HashMap::new()), content.len());
+        let response = get(&app, &path).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["Content-Length"],
+            content.len().to_string()
+        );
+        assert_eq!(response.body().await, content.as_slice());
+
+        let response = get_with_headers(&app, &path, &[("Range", "bytes=-2")]).await;
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers()["Content-Length"], "2");
+        assert_eq!(response.body().await, &content[content.len() - 2..]);
+
+        let app = super::router(
+            profile.to_owned(),
+            Arc::new(HashMap::new()),
+            content.len() - 1,
+        );
+        let response = get(&app, &path).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_subdirectory_archives() {
+        let tmp = tempfile::tempdir().unwrap();
+        let ctx = test::seed(tmp.path());
+        let app = super::router(
+            ctx.profile().to_owned(),
+            Arc::new(HashMap::new()),
+            usize::MAX,
+        );
+        let contains = |haystack: &[u8], needle: &str| {
+            haystack
+                .windows(needle.len())
+                .any(|window| window == needle.as_bytes())
+        };
+
+        let response = get(&app, format!("/{RID}/archive/master/dir1.tar")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()["Content-Disposition"],
+            "attachment; filename=\"hello-world-master-dir1.tar\""
+        );
+        let body = response.body().await;
+        assert!(contains(&body, "hello-world-master-dir1/dir1/README"));
+        assert!(!contains(&body, "hello-world-master-dir1/README"));
+
+        let response = get(&app, format!("/{RID}/archive/master/dir1/README.tar")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = get(&app, format!("/{RID}/archive/master/dir2.tar")).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        for (extension, mime, magic) in [
+            ("tar.xz", "application/x-xz", &b"\xfd7zXZ\0"[..]),
+            ("tar.zst", "application/zstd", &b"\x28\xb5\x2f\xfd"[..]),
+        ] {
+            let path = format!("/{RID}/archive/master.{extension}");
+            let response = get(&app, &path).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["Content-Type"], mime);
+            let first = response.body().await;
+            assert!(first.starts_with(magic));
+
+            // Archives are the same, byte for byte, every time.
+            let second = get(&app, &path).await.body().await;
+            assert_eq!(first, second);
+        }
+    }
 }
@@ -326,7 +326,14 @@ fn seed_with_signer(dir: &Path, profile: radicle::Profile, signer: &SigningKey)
         aliases: std::collections::HashMap::new(),
         listen: axum_listener::DualAddr::Tcp(std::net::SocketAddr::from(([0, 0, 0, 0], 8080))),
         cache: Some(crate::DEFAULT_CACHE_SIZE),
+        cache_dir: None,
         search: None,
+        write: false,
+        blocking: crate::BlockingOptions::default(),
+        rate_limit: crate::RateLimitOptions::default(),
+        git: crate::GitOptions::default(),
+        bundle_dir: None,
+        max_blob_size: crate::DEFAULT_MAX_BLOB_SIZE,
     };
 
     let web_config = crate::api::WebConfig::from_profile(&profile);
@@ -474,17 +481,92 @@ pub fn seed_multi_peer(dir: &Path) -> Context {
 pub async fn get(app: &Router, path: impl ToString) -> Response {
     Response(
         app.clone()
-            .oneshot(request(path, Method::GET, None))
+            .oneshot(request(path, Method::GET, None, None))
             .await
             .unwrap(),
     )
 }
 
-fn request(path: impl ToString, method: Method, body: Option<Body>) -> Request<Body> {
-    let request = Request::builder()
+/// Send a `GET` request with additional headers.
+pub async fn get_with_headers(
+    app: &Router,
+    path: impl ToString,
+    headers: &[(&str, &str)],
+) -> Response {
+    let mut request = request(path, Method::GET, None, None);
+    for (name, value) in headers {
+        request.headers_mut().insert(
+            axum::http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            axum::http::HeaderValue::from_str(value).unwrap(),
+        );
+    }
+    Response(app.clone().oneshot(request).await.unwrap())
+}
+
+pub async fn post(
+    app: &Router,
+    path: impl ToString,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::POST, body, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn put(
+    app: &Router,
+    path: impl ToString,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::PUT, body, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn patch(
+    app: &Router,
+    path: impl ToString,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::PATCH, body, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+pub async fn delete(app: &Router, path: impl ToString, auth: Option<String>) -> Response {
+    Response(
+        app.clone()
+            .oneshot(request(path, Method::DELETE, None, auth))
+            .await
+            .unwrap(),
+    )
+}
+
+fn request(
+    path: impl ToString,
+    method: Method,
+    body: Option<Body>,
+    auth: Option<String>,
+) -> Request<Body> {
+    let mut request = Request::builder()
         .method(method)
         .uri(path.to_string())
         .header("Content-Type", "application/json");
+    if let Some(token) = auth {
+        request = request.header("Authorization", format!("Bearer {token}"));
+    }
 
     request.body(body.unwrap_or_else(Body::empty)).unwrap()
 }
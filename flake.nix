@@ -141,6 +141,7 @@
                 git
                 asciidoctor
                 installShellFiles
+                makeWrapper
               ]
               ++ lib.optionals pkgs.stdenv.isDarwin (with pkgs; [
                 libiconv
@@ -166,6 +167,9 @@
                 asciidoctor -d manpage -b manpage $page
                 installManPage ''${page::-5}
               done
+              # Serving git and compressing .tar.xz and .tar.zst archives.
+              wrapProgram $out/bin/radicle-httpd \
+                --prefix PATH : ${pkgs.lib.makeBinPath (with pkgs; [git xz zstd])}
             '';
           });
 